@@ -5,6 +5,7 @@ use chip::{
 };
 use criterion::{criterion_group, criterion_main, Criterion};
 
+#[allow(clippy::redundant_static_lifetimes)]
 const ROM_NAME: &'static str = "15PUZZLE";
 
 static BASE_ROM: once_cell::sync::Lazy<Rom> = once_cell::sync::Lazy::new(|| get_rom(ROM_NAME));
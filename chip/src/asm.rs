@@ -0,0 +1,414 @@
+//! A minimal assembler, that turns the mnemonics of the [`opcode`](crate::opcode)
+//! catalog back into rom data.
+//!
+//! The source is read line by line, where each line may contain
+//! - a label (`loop:`), that can be used in place of an address,
+//! - an instruction using the mnemonics of [`supported_opcodes`](supported_opcodes), e.g.
+//!   `DRW V0, V1, 5`, or `DW` followed by a comma separated list of data words,
+//! - a comment starting with `;`.
+//!
+//! Numbers are either decimal or hexadecimal with a `0x` or `#` prefix.
+
+use hashbrown::HashMap;
+
+use crate::{
+    definitions::cpu,
+    opcode::{supported_opcodes, Mode, Opcode, OpcodeInfo},
+    AsmError,
+};
+
+/// The directive used to emit raw data words.
+const DATA_WORD: &str = "DW";
+
+/// A single statement of the source.
+struct Statement<'a> {
+    /// The line (starting at `1`) of the statement.
+    line: usize,
+    /// The name of the instruction, e.g. `LD`.
+    name: &'a str,
+    /// The comma separated operands.
+    operands: Vec<&'a str>,
+}
+
+impl Statement<'_> {
+    /// The amount of bytes this statement will occupy in the rom.
+    fn size(&self) -> usize {
+        if self.name.eq_ignore_ascii_case(DATA_WORD) {
+            self.operands.len() * 2
+        } else {
+            2
+        }
+    }
+}
+
+/// Will assemble the source into the rom data, that is loaded at the
+/// [`PROGRAM_COUNTER`](cpu::PROGRAM_COUNTER).
+///
+/// # Example
+/// ```rust
+/// # use chip::asm::assemble;
+/// let rom = assemble(
+///     "start: LD V0, 0x0A ; load ten
+///            JP start",
+/// )
+/// .unwrap();
+/// assert_eq!(vec![0x60, 0x0A, 0x12, 0x00], rom);
+/// ```
+pub fn assemble(source: &str) -> Result<Vec<u8>, AsmError> {
+    assemble_at(source, cpu::PROGRAM_COUNTER)
+}
+
+/// Will assemble the source into data, that is loaded at the given address, so that the
+/// labels resolve to the addresses within that data.
+///
+/// # Example
+/// ```rust
+/// # use chip::asm::assemble_at;
+/// let data = assemble_at("start: JP start", 0x300).unwrap();
+/// assert_eq!(vec![0x13, 0x00], data);
+/// ```
+pub fn assemble_at(source: &str, origin: usize) -> Result<Vec<u8>, AsmError> {
+    let opcodes = supported_opcodes(Mode::Chip8);
+    let (labels, statements) = parse(source, origin)?;
+
+    let mut rom = Vec::new();
+    for statement in statements {
+        if statement.name.eq_ignore_ascii_case(DATA_WORD) {
+            for operand in &statement.operands {
+                let word = value(operand, 0xFFFF, &labels, statement.line)?;
+                rom.extend_from_slice(&(word as Opcode).to_be_bytes());
+            }
+        } else {
+            let opcode = encode_statement(&opcodes, &statement, &labels)?;
+            rom.extend_from_slice(&opcode.to_be_bytes());
+        }
+    }
+
+    Ok(rom)
+}
+
+/// Will split the source into the statements and collect the addresses of all labels,
+/// counting from the origin.
+fn parse(
+    source: &str,
+    origin: usize,
+) -> Result<(HashMap<&str, usize>, Vec<Statement<'_>>), AsmError> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = origin;
+
+    for (line, text) in source.lines().enumerate() {
+        let line = line + 1;
+        let mut text = text.split(';').next().unwrap_or_default().trim();
+
+        if let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if is_identifier(label) {
+                if labels.insert(label, address).is_some() {
+                    return Err(AsmError::DuplicateLabel {
+                        line,
+                        label: label.to_string(),
+                    });
+                }
+                text = rest.trim();
+            }
+        }
+
+        if text.is_empty() {
+            continue;
+        }
+
+        let (name, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let operands = match operands.trim() {
+            "" => Vec::new(),
+            operands => operands.split(',').map(str::trim).collect(),
+        };
+        let statement = Statement {
+            line,
+            name,
+            operands,
+        };
+        address += statement.size();
+        statements.push(statement);
+    }
+
+    Ok((labels, statements))
+}
+
+/// Will encode the statement using the first opcode, whose mnemonic fits the operands.
+fn encode_statement(
+    opcodes: &[OpcodeInfo],
+    statement: &Statement<'_>,
+    labels: &HashMap<&str, usize>,
+) -> Result<Opcode, AsmError> {
+    let mut candidates = opcodes
+        .iter()
+        .filter(|info| {
+            info.mnemonic
+                .split_whitespace()
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case(statement.name))
+        })
+        .peekable();
+
+    if candidates.peek().is_none() {
+        return Err(AsmError::UnknownInstruction {
+            line: statement.line,
+            name: statement.name.to_string(),
+        });
+    }
+
+    let keywords = keywords(opcodes);
+    let info = candidates
+        .find(|info| {
+            let templates = templates(info);
+            templates.len() == statement.operands.len()
+                && templates
+                    .iter()
+                    .zip(&statement.operands)
+                    .all(|(template, operand)| fits(template, operand, &keywords))
+        })
+        .ok_or_else(|| AsmError::InvalidOperands {
+            line: statement.line,
+            operands: statement.operands.join(", "),
+        })?;
+
+    encode(info, &statement.operands, labels, statement.line)
+}
+
+/// Will return the operand templates of the mnemonic, e.g. `["VX", "VY", "N"]`.
+fn templates(info: &OpcodeInfo) -> Vec<&'static str> {
+    match info.mnemonic.split_once(' ') {
+        None => Vec::new(),
+        Some((_, operands)) => operands.split(',').map(str::trim).collect(),
+    }
+}
+
+/// Will return all the fixed operands used in the mnemonics, e.g. `DT` or `[I]`.
+fn keywords(opcodes: &[OpcodeInfo]) -> Vec<&'static str> {
+    opcodes
+        .iter()
+        .flat_map(templates)
+        .filter(|template| !is_register_template(template) && !is_value_template(template))
+        .collect()
+}
+
+/// Checks if the operand fits the given template.
+fn fits(template: &str, operand: &str, keywords: &[&str]) -> bool {
+    if is_register_template(template) {
+        register(operand).is_some()
+    } else if is_value_template(template) {
+        register(operand).is_none() && !keywords.iter().any(|k| k.eq_ignore_ascii_case(operand))
+    } else {
+        template.eq_ignore_ascii_case(operand)
+    }
+}
+
+/// Will build the opcode from the pattern, with the operands filled in.
+fn encode(
+    info: &OpcodeInfo,
+    operands: &[&str],
+    labels: &HashMap<&str, usize>,
+    line: usize,
+) -> Result<Opcode, AsmError> {
+    let mut opcode = info.pattern.chars().fold(0, |opcode, c| {
+        opcode << 4 | c.to_digit(16).unwrap_or(0) as Opcode
+    });
+
+    for (template, operand) in templates(info).iter().zip(operands) {
+        let (shift, max) = match *template {
+            "VX" => (8, 0xF),
+            "VY" => (4, 0xF),
+            "N" => (0, 0xF),
+            "NN" => (0, 0xFF),
+            "NNN" => (0, 0xFFF),
+            _ => continue,
+        };
+        let value = match register(operand) {
+            Some(register) => register,
+            None => value(operand, max, labels, line)?,
+        };
+        opcode |= (value as Opcode) << shift;
+    }
+
+    Ok(opcode)
+}
+
+/// Will resolve the operand to a number, that is at most `max`.
+fn value(
+    operand: &str,
+    max: usize,
+    labels: &HashMap<&str, usize>,
+    line: usize,
+) -> Result<usize, AsmError> {
+    let value = match number(operand) {
+        Some(value) => value,
+        None if is_identifier(operand) => {
+            *labels.get(operand).ok_or_else(|| AsmError::UnknownLabel {
+                line,
+                label: operand.to_string(),
+            })?
+        }
+        None => {
+            return Err(AsmError::InvalidOperands {
+                line,
+                operands: operand.to_string(),
+            })
+        }
+    };
+
+    if value > max {
+        return Err(AsmError::ValueOutOfRange { line, value });
+    }
+    Ok(value)
+}
+
+fn is_register_template(template: &str) -> bool {
+    matches!(template, "VX" | "VY")
+}
+
+fn is_value_template(template: &str) -> bool {
+    matches!(template, "N" | "NN" | "NNN")
+}
+
+/// Will parse a register operand like `VA` into its index.
+fn register(operand: &str) -> Option<usize> {
+    let mut chars = operand.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some('V' | 'v'), Some(index), None) => index.to_digit(16).map(|index| index as usize),
+        _ => None,
+    }
+}
+
+/// Will parse a decimal or hexadecimal (`0x` or `#` prefixed) number.
+fn number(operand: &str) -> Option<usize> {
+    match operand
+        .strip_prefix("0x")
+        .or_else(|| operand.strip_prefix("0X"))
+        .or_else(|| operand.strip_prefix('#'))
+    {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => operand.parse().ok(),
+    }
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+    use crate::opcode::Opcodes;
+
+    #[test]
+    fn test_assemble() {
+        let source = "
+            ; draws the font of V0 forever
+            start:
+                CLS
+                LD V0, #A
+                LD F, V0
+                LD V1, 0x10     ; x
+                LD V2, 8        ; y
+            loop: DRW V1, V2, 5
+                JP loop
+            data: DW 0xF00F, start
+        ";
+        let expected = vec![
+            0x00, 0xE0, 0x60, 0x0A, 0xF0, 0x29, 0x61, 0x10, 0x62, 0x08, 0xD1, 0x25, 0x12, 0x0A,
+            0xF0, 0x0F, 0x02, 0x00,
+        ];
+        assert_eq!(Ok(expected), assemble(source));
+    }
+
+    #[test]
+    fn test_assemble_catalog() {
+        // every mnemonic has to be assembled into an opcode of the matching pattern
+        for info in supported_opcodes(Mode::Chip8) {
+            let operands: Vec<_> = templates(&info)
+                .into_iter()
+                .map(|template| match template {
+                    "VX" => "V1",
+                    "VY" => "V2",
+                    "NNN" => "0x345",
+                    "NN" => "0x45",
+                    "N" => "5",
+                    keyword => keyword,
+                })
+                .collect();
+            let name = info.mnemonic.split(' ').next().unwrap();
+            let source = format!("{} {}", name, operands.join(", "));
+            let rom = assemble(&source).expect(info.mnemonic);
+            let opcode = Opcode::from_be_bytes([rom[0], rom[1]]);
+
+            assert_eq!(
+                info.pattern,
+                format!("{:04X}", opcode)
+                    .chars()
+                    .zip(info.pattern.chars())
+                    .map(|(got, pattern)| match pattern {
+                        'X' | 'Y' | 'N' => pattern,
+                        _ => got,
+                    })
+                    .collect::<String>(),
+                "{}",
+                info.mnemonic
+            );
+            assert!(Opcodes::try_from(opcode).is_ok(), "{}", info.mnemonic);
+        }
+
+        // different forms of the same instruction
+        assert_eq!(Ok(vec![0xB3, 0x45]), assemble("JP V0, 0x345"));
+        assert_eq!(Ok(vec![0xF3, 0x0A]), assemble("ld v3, k"));
+        assert_eq!(Ok(vec![0x83, 0x40]), assemble("LD V3, V4"));
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        let err = assemble("CLS\nFOO V1").unwrap_err();
+        assert_eq!(2, err.line());
+        assert_eq!(
+            AsmError::UnknownInstruction {
+                line: 2,
+                name: "FOO".to_string()
+            },
+            err
+        );
+
+        assert_eq!(
+            Err(AsmError::InvalidOperands {
+                line: 1,
+                operands: "V1".to_string()
+            }),
+            assemble("CLS V1")
+        );
+        assert_eq!(
+            Err(AsmError::ValueOutOfRange {
+                line: 1,
+                value: 0x100
+            }),
+            assemble("LD V1, 256")
+        );
+        assert_eq!(
+            Err(AsmError::UnknownLabel {
+                line: 2,
+                label: "end".to_string()
+            }),
+            assemble("\nJP end")
+        );
+        assert_eq!(
+            Err(AsmError::DuplicateLabel {
+                line: 2,
+                label: "a".to_string()
+            }),
+            assemble("a: CLS\na: CLS")
+        );
+    }
+}
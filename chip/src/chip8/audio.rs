@@ -0,0 +1,41 @@
+//! The sound generation written for the [`chipset`](super::ChipSet).
+//! This implementation was split up into this file for smaller file sizes and higher
+//! cohesion.
+
+use super::ChipSet;
+use crate::{
+    definitions::sound,
+    timer::{TimedWorker, TimerCallback},
+};
+
+impl<W, S> ChipSet<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    /// Will generate `frames` mono samples at the given `sample_rate`, which can be
+    /// passed to an audio device as is.
+    ///
+    /// While the sound timer is running a square wave is generated, otherwise silence.
+    /// The phase of the wave is kept between the calls, so that consecutive buffers
+    /// can be played back without any clicks.
+    pub fn audio_samples(&mut self, sample_rate: u32, frames: usize) -> Vec<f32> {
+        if self.get_sound_timer() == 0 || sample_rate == 0 {
+            return vec![0.0; frames];
+        }
+
+        let step = sound::FREQUENCY / sample_rate as f32;
+
+        (0..frames)
+            .map(|_| {
+                let sample = if self.audio_phase < 0.5 {
+                    sound::AMPLITUDE
+                } else {
+                    -sound::AMPLITUDE
+                };
+                self.audio_phase = (self.audio_phase + step).fract();
+                sample
+            })
+            .collect()
+    }
+}
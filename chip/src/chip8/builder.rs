@@ -0,0 +1,172 @@
+//! The builder used to configure a [`chipset`](super::ChipSet) before it is created.
+
+use super::ChipSet;
+use crate::{
+    definitions::{cpu, timer},
+    devices::Keyboard,
+    opcode::Mode,
+    quirks::Quirks,
+    resources::Rom,
+    timer::{TimedWorker, TimerCallback},
+};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// Configures and creates a [`ChipSet`](ChipSet).
+///
+/// # Example
+/// ```rust
+/// # use chip::{chip8::{ChipSet, ChipSetBuilder}, resources::RomArchives, timer::{NoCallback, Worker}};
+/// let rom = RomArchives::new().get_file_data("IBMLOGO").unwrap();
+/// let chip: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(rom).with_timer_hz(120).build();
+/// ```
+pub struct ChipSetBuilder {
+    /// The rom loaded into the chip.
+    pub(super) rom: Rom,
+    /// The keyboard read by the chip.
+    pub(super) keyboard: Arc<RwLock<Keyboard>>,
+    /// The frequency in hertz at which the delay and sound timer count down.
+    pub(super) timer_hz: u64,
+    /// The amount of program counter values retained for tracing.
+    pub(super) pc_history: usize,
+    /// The amount of cycles a polled key is suppressed for.
+    pub(super) key_debounce_cycles: usize,
+    /// If the display is double buffered.
+    pub(super) double_buffering: bool,
+    /// If the execution of sprite data is detected.
+    pub(super) data_execution_check: bool,
+    /// If the pixels toggled by a draw are recorded.
+    pub(super) draw_deltas: bool,
+    /// If sprites are drawn row by row.
+    pub(super) slow_draw: bool,
+    /// The behaviours of the emulated interpreter.
+    pub(super) quirks: Quirks,
+    /// The amount of data registers addressable by the opcodes.
+    pub(super) register_count: usize,
+}
+
+impl ChipSetBuilder {
+    /// Creates a new builder for a chip running the given rom.
+    pub fn new(rom: Rom) -> Self {
+        Self {
+            rom,
+            keyboard: Arc::new(RwLock::new(Keyboard::new())),
+            timer_hz: timer::HERZ as u64,
+            pc_history: 0,
+            key_debounce_cycles: 0,
+            double_buffering: false,
+            data_execution_check: false,
+            draw_deltas: false,
+            slow_draw: false,
+            quirks: Quirks::default(),
+            register_count: cpu::register::SIZE,
+        }
+    }
+
+    /// Will use the given external keyboard.
+    pub fn with_keyboard(mut self, keyboard: Arc<RwLock<Keyboard>>) -> Self {
+        self.keyboard = keyboard;
+        self
+    }
+
+    /// Will set the frequency at which both the delay and the sound timer count down,
+    /// by default this is [`60Hz`](timer::HERZ). The timers run with a resolution of
+    /// milliseconds, so that the frequency has to be between `1Hz` and `1000Hz`.
+    pub fn with_timer_hz(mut self, hz: u64) -> Self {
+        self.timer_hz = hz;
+        self
+    }
+
+    /// Will retain the last `capacity` program counter values, so that the recent
+    /// execution path can be inspected after a crash. By default this is `0`, which
+    /// disables the history.
+    pub fn with_pc_history(mut self, capacity: usize) -> Self {
+        self.pc_history = capacity;
+        self
+    }
+
+    /// Will suppress the auto repeat of keys polled by `EX9E` and `EXA1`, so that a
+    /// held key only reads as pressed on the first poll within a window of `cycles`
+    /// executed opcodes. This slows down games, that move a step per poll, on fast hosts.
+    /// By default this is `0`, which disables the debounce.
+    pub fn with_key_debounce_cycles(mut self, cycles: usize) -> Self {
+        self.key_debounce_cycles = cycles;
+        self
+    }
+
+    /// Will double buffer the display, so that the opcodes draw into a back buffer and
+    /// [`get_display`](ChipSet::get_display) only changes once the host calls
+    /// [`present`](ChipSet::present). Presenting once per frame removes most of the
+    /// flicker of the XOR drawing. By default this is disabled, so that every draw is
+    /// visible right away.
+    pub fn with_double_buffering(mut self, enabled: bool) -> Self {
+        self.double_buffering = enabled;
+        self
+    }
+
+    /// Will record the memory read as sprite data by `DXYN` and warn, once the program
+    /// counter enters such a region, as this usually means that a rom executes its data
+    /// by accident. The addresses are available with
+    /// [`data_executions`](ChipSet::data_executions). By default this is disabled, as it
+    /// slows down the execution.
+    pub fn with_data_execution_check(mut self, enabled: bool) -> Self {
+        self.data_execution_check = enabled;
+        self
+    }
+
+    /// Will record the coordinates of every pixel toggled by the last `DXYN`, so that a
+    /// frontend can transmit only the changes of the display. The coordinates are
+    /// available with [`toggled_pixels`](ChipSet::toggled_pixels). By default this is
+    /// disabled, as it slows down the drawing.
+    pub fn with_draw_deltas(mut self, enabled: bool) -> Self {
+        self.draw_deltas = enabled;
+        self
+    }
+
+    /// Will draw the sprites row by row, so that a `DXYN` is executed over `N` steps, each
+    /// drawing a single row and returning its own [`Draw`](crate::opcode::Operation::Draw).
+    /// This emulates the look of a slow sprite rendering, when the host presents the
+    /// display after every draw. By default this is disabled, so that a sprite is drawn
+    /// at once.
+    pub fn with_slow_draw(mut self, enabled: bool) -> Self {
+        self.slow_draw = enabled;
+        self
+    }
+
+    /// Will set the instruction set executed by the chip, opcodes of other instruction
+    /// sets are reported as unsupported. By default this is [`Chip8`](Mode::Chip8).
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.quirks.mode = mode;
+        self
+    }
+
+    /// Will set all the behaviours of the emulated interpreter at once, including the
+    /// [`mode`](Self::with_mode).
+    pub fn with_quirks(mut self, quirks: Quirks) -> Self {
+        self.quirks = quirks;
+        self
+    }
+
+    /// Will limit the data registers, that the `X` and `Y` operands may address, to the
+    /// first `count` registers starting at `V0`. Opcodes addressing a register outside of the bank
+    /// fail with [`InvalidRegister`](crate::ProcessError::InvalidRegister), this is meant
+    /// for experimental variants with a reduced register bank. By default all the
+    /// [`16`](cpu::register::SIZE) registers are available.
+    pub fn with_register_count(mut self, count: usize) -> Self {
+        self.register_count = count;
+        self
+    }
+
+    /// Will create the configured chip.
+    ///
+    /// # Panics
+    /// If the timer frequency is not between `1Hz` and `1000Hz` or the register count is
+    /// not between `1` and [`16`](cpu::register::SIZE).
+    pub fn build<W, S>(self) -> ChipSet<W, S>
+    where
+        W: TimedWorker,
+        S: TimerCallback + 'static,
+    {
+        ChipSet::from_builder(self)
+    }
+}
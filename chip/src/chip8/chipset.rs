@@ -2,14 +2,19 @@
 //! The given implementation is based primatily on the [wikipedia
 //! page](https://en.wikipedia.org/wiki/CHIP-8) definitions.
 
+use super::{ChipSetBuilder, Event, Frame};
 use crate::{
-    definitions::{cpu, display, keyboard, memory, timer},
+    constants::{FONT_CHARACTER_SIZE, REGISTER_SIZE},
+    definitions::{cpu, display, keyboard, memory},
     devices::Keyboard,
-    opcode::{self, ChipOpcodePreProcessHandler, Opcodes, ProgramCounter, ProgramCounterStep},
+    opcode::{
+        self, ChipOpcodePreProcessHandler, Opcode, Opcodes, ProgramCounter, ProgramCounterStep,
+    },
+    quirks::{MemoryBounds, Quirks, SysCall},
     resources::Rom,
-    timer::{NoCallback, TimerCallback},
+    timer::{ChangeCallback, NoCallback, TimerCallback},
     timer::{TimedWorker, Timer, TimerValue},
-    OpcodeError, ProcessError, StackError,
+    ChipError, OpcodeError, ProcessError, StackError,
 };
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use rand::RngCore;
@@ -18,6 +23,45 @@ use tinyvec::ArrayVec;
 
 use hashbrown::HashMap;
 
+/// The amount of pixels packed into a byte by
+/// [`export_display`](ChipSet::export_display).
+const PIXELS_PER_BYTE: usize = 8;
+
+/// Will check that the rom fits into the memory behind the
+/// [`PROGRAM_COUNTER`](cpu::PROGRAM_COUNTER).
+fn check_rom_size(rom: &Rom) -> Result<(), ChipError> {
+    let max = memory::SIZE - cpu::PROGRAM_COUNTER;
+    if rom.len() > max {
+        return Err(ChipError::RomSize {
+            size: rom.len(),
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Will return the time between two ticks of a timer running at the given frequency.
+/// The interval is calculated in nanoseconds, as whole milliseconds would make e.g.
+/// 60Hz tick every 16ms, which is 62.5Hz.
+pub(super) fn timer_interval(timer_hz: u64) -> Duration {
+    Duration::from_nanos(1_000_000_000 / timer_hz)
+}
+
+/// The callback stored by the chipset, that is run before the next opcode is executed.
+pub(super) type PreProcessor = Box<dyn FnOnce(&mut InternalChipSet) + Send>;
+
+/// The progress of a sprite drawn row by row, the coordinates are kept, as the draw
+/// might change `VF`.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct PendingDraw {
+    /// The next row of the sprite to draw.
+    pub(super) row: usize,
+    /// The display column of the sprite.
+    pub(super) x: usize,
+    /// The display row of the sprite.
+    pub(super) y: usize,
+}
+
 /// The chipset struct containing the internal implementation of the chipset
 /// and the timers.
 /// The struct has been split up into two instances to simplyfiy the implementation.
@@ -27,12 +71,17 @@ where
     S: TimerCallback,
 {
     /// The actuall chipset implementation.
-    chipset: InternalChipSet,
+    pub(super) chipset: InternalChipSet,
     /// Holds the delaytimer struct, so that the internal closures do not go out of scope and
-    /// then drop.
-    _delay_timer: Timer<W, u8, NoCallback>,
+    /// then drop. It is used to pause the timer as well.
+    delay_timer: Timer<W, u8, NoCallback>,
     /// Holds the sound timer struct, so that the internally used closures will not be dropped.
-    _sound_timer: Timer<W, u8, S>,
+    /// It is used to pause the timer as well.
+    sound_timer: Timer<W, u8, S>,
+    /// The phase of the generated tone, so that it continues seamlessly between calls.
+    pub(super) audio_phase: f32,
+    /// The frequency in hertz at which the timers count down.
+    timer_hz: u64,
 }
 
 impl<W, S> ChipSet<W, S>
@@ -42,20 +91,109 @@ where
 {
     /// Creates a new chip set from a given rom.
     pub fn new(rom: Rom) -> Self {
-        Self::with_keyboard(rom, Arc::new(RwLock::new(Keyboard::new())))
+        ChipSetBuilder::new(rom).build()
     }
 
-    /// Crates a new chip with an external keyboard.
+    /// Creates a new chip set without a program, where the memory only contains the
+    /// fontset and the program counter is at the [`PROGRAM_COUNTER`](cpu::PROGRAM_COUNTER),
+    /// e.g. to show a blank display before the user picked a rom. The rom can then be
+    /// loaded with [`fork_with_rom`](Self::fork_with_rom). Executing the empty program
+    /// fails with an unsupported opcode.
+    pub fn new_empty() -> Self {
+        Self::new(Rom::from_bytes("", &[]))
+    }
+
+    /// Creates a new chip set from a given rom, checking that the rom fits into the memory
+    /// instead of panicking.
+    pub fn try_new(rom: Rom) -> Result<Self, ChipError> {
+        check_rom_size(&rom)?;
+        Ok(Self::new(rom))
+    }
+
+    /// Creates a new chip running the given rom with the same configuration as this
+    /// one, so that different roms can be compared under the same settings. The new
+    /// chip shares the keyboard with this one, while the callbacks and the instruction
+    /// budget are not carried over.
+    pub fn fork_with_rom(&self, rom: Rom) -> Result<Self, ChipError> {
+        check_rom_size(&rom)?;
+        let chipset = &self.chipset;
+        let builder = ChipSetBuilder::new(rom)
+            .with_keyboard(chipset.keyboard.clone())
+            .with_timer_hz(self.timer_hz)
+            .with_pc_history(chipset.pc_history_capacity)
+            .with_key_debounce_cycles(chipset.key_debounce_cycles)
+            .with_double_buffering(chipset.front_display.is_some())
+            .with_data_execution_check(chipset.sprite_reads.is_some())
+            .with_draw_deltas(chipset.toggled_pixels.is_some())
+            .with_slow_draw(chipset.slow_draw)
+            .with_quirks(chipset.quirks)
+            .with_register_count(chipset.register_count);
+        let mut fork = builder.build();
+        fork.set_key_mask(chipset.key_mask);
+        Ok(fork)
+    }
+
+    /// Creates a new chip with an external keyboard.
     pub fn with_keyboard(rom: Rom, keyboard: Arc<RwLock<Keyboard>>) -> Self {
-        let (delay_timer, delay_value) = Timer::new(0, Duration::from_millis(timer::INTERVAL));
-        let (sound_timer, sound_value) =
-            Timer::with_callback(0, Duration::from_millis(timer::INTERVAL), S::new());
-        let chipset = InternalChipSet::new(rom, delay_value, sound_value, keyboard);
+        ChipSetBuilder::new(rom).with_keyboard(keyboard).build()
+    }
+
+    /// Creates a new chip with an external keyboard, checking that the rom fits into the
+    /// memory instead of panicking.
+    pub fn try_with_keyboard(rom: Rom, keyboard: Arc<RwLock<Keyboard>>) -> Result<Self, ChipError> {
+        check_rom_size(&rom)?;
+        Ok(Self::with_keyboard(rom, keyboard))
+    }
+
+    /// Creates the chip configured by the given builder.
+    pub(super) fn from_builder(
+        ChipSetBuilder {
+            rom,
+            keyboard,
+            timer_hz,
+            pc_history,
+            key_debounce_cycles,
+            double_buffering,
+            data_execution_check,
+            draw_deltas,
+            slow_draw,
+            quirks,
+            register_count,
+        }: ChipSetBuilder,
+    ) -> Self {
+        assert!(
+            (1..=1000).contains(&timer_hz),
+            "The timers require a frequency between 1Hz and 1000Hz."
+        );
+        assert!(
+            (1..=cpu::register::SIZE).contains(&register_count),
+            "The register bank requires between 1 and 16 registers."
+        );
+        let interval = timer_interval(timer_hz);
+        let (delay_timer, delay_value) = Timer::new(0, interval);
+        let (sound_timer, sound_value) = Timer::with_callback(0, interval, S::new());
+        let mut chipset = InternalChipSet::new(rom, delay_value, sound_value, keyboard);
+        chipset.pc_history_capacity = pc_history;
+        chipset.key_debounce_cycles = key_debounce_cycles;
+        chipset.quirks = quirks;
+        if double_buffering {
+            chipset.front_display = Some(chipset.display.clone());
+        }
+        if data_execution_check {
+            chipset.sprite_reads = Some(vec![false; memory::SIZE]);
+        }
+        if draw_deltas {
+            chipset.toggled_pixels = Some(Vec::new());
+        }
+        chipset.slow_draw = slow_draw;
+        chipset.register_count = register_count;
 
         Self {
             chipset,
-            _delay_timer: delay_timer,
-            _sound_timer: sound_timer,
+            delay_timer,
+            sound_timer,
+            audio_phase: 0.0,
+            timer_hz,
         }
     }
 
@@ -64,9 +202,148 @@ where
         self.chipset.get_display()
     }
 
+    /// Will return the display as RGBA buffer row by row, with `4` bytes per pixel, so
+    /// that it can be uploaded as texture (e.g. into a canvas `ImageData`) directly.
+    /// Pixels that are on use the `fg` color, the others the `bg` color.
+    pub fn display_rgba(&self, fg: [u8; 4], bg: [u8; 4]) -> Vec<u8> {
+        self.get_display()
+            .iter()
+            .flatten()
+            .flat_map(|&pixel| if pixel { fg } else { bg })
+            .collect()
+    }
+
+    /// Will pack the display into bytes, row by row with `8` pixels per byte, where the
+    /// most significant bit is the leftmost pixel. This is much lighter than a
+    /// [`snapshot`](Self::snapshot), if only the screen is of interest.
+    pub fn export_display(&self) -> Vec<u8> {
+        self.get_display()
+            .iter()
+            .flat_map(|row| row.chunks(PIXELS_PER_BYTE))
+            .map(|pixels| {
+                pixels
+                    .iter()
+                    .fold(0, |byte, &pixel| (byte << 1) | pixel as u8)
+            })
+            .collect()
+    }
+
+    /// Will replace the display with the data packed by
+    /// [`export_display`](Self::export_display), e.g. to set up a known screen for a test.
+    /// A double buffered display is presented right away.
+    ///
+    /// Fails with [`DisplaySize`](ProcessError::DisplaySize), if the data does not
+    /// contain exactly the pixels of the display.
+    pub fn import_display(&mut self, data: &[u8]) -> Result<(), ProcessError> {
+        let expected = display::RESOLUTION / PIXELS_PER_BYTE;
+        if data.len() != expected {
+            return Err(ProcessError::DisplaySize {
+                size: data.len(),
+                expected,
+            });
+        }
+
+        let chip = &mut self.chipset;
+        let bytes = data.chunks(display::HEIGHT / PIXELS_PER_BYTE);
+        for (row, bytes) in chip.display.iter_mut().zip(bytes) {
+            for (pixels, byte) in row.chunks_mut(PIXELS_PER_BYTE).zip(bytes) {
+                for (i, pixel) in pixels.iter_mut().enumerate() {
+                    *pixel = byte & (0x80 >> i) != 0;
+                }
+            }
+        }
+        chip.present();
+        Ok(())
+    }
+
+    /// Will return the font region of the memory, containing the `4x5` sprites of the
+    /// hex digits `0-F` one after another, as read by the program.
+    pub fn fontset(&self) -> &[u8] {
+        let start = display::fontset::LOCATION;
+        &self.chipset.memory[start..(start + display::fontset::FONTSET.len())]
+    }
+
+    /// Will return the `5` bytes of the font sprite of the given hex digit, that
+    /// `FX29` points to, or `None` if the digit is larger than `0xF`. Each byte is a row,
+    /// where the upper `4` bits are the pixels.
+    pub fn font_sprite(&self, digit: u8) -> Option<&[u8]> {
+        self.fontset()
+            .chunks_exact(FONT_CHARACTER_SIZE)
+            .nth(digit as usize)
+    }
+
+    /// Will return the pixels, that were turned off by a collision during the last
+    /// `DXYN`, row by row. This is meant for debugging sprite overlaps.
+    pub fn last_collision(&self) -> &[bool] {
+        &self.chipset.last_collision
+    }
+
+    /// Will return the `(x, y)` coordinates of every pixel toggled by the last `DXYN`,
+    /// both the ones turned on and off, if the recording was enabled with
+    /// [`with_draw_deltas`](ChipSetBuilder::with_draw_deltas).
+    pub fn toggled_pixels(&self) -> Option<&[(usize, usize)]> {
+        self.chipset.toggled_pixels.as_deref()
+    }
+
+    /// Will return the patterns (e.g. `DXYN`) of all the opcodes executed since the chip
+    /// was created or reset, so that the coverage of a test rom can be checked.
+    #[cfg(feature = "metrics")]
+    pub fn executed_opcode_kinds(&self) -> &std::collections::HashSet<&'static str> {
+        &self.chipset.executed_opcode_kinds
+    }
+
+    /// Will return the counters of the draws, calls and jumps executed since the chip
+    /// was created or reset.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> super::Stats {
+        self.chipset.stats
+    }
+
+    /// Will return how often the opcode at each address was executed since the chip was
+    /// created or reset, so that the hot code of a rom can be found, e.g. by annotating
+    /// the [`disassembly`](Rom::disassembly).
+    #[cfg(feature = "metrics")]
+    pub fn execution_profile(&self) -> &std::collections::HashMap<usize, u64> {
+        &self.chipset.execution_profile
+    }
+
+    /// Will return the addresses at which sprite data was executed as opcode, if the
+    /// check was enabled with
+    /// [`with_data_execution_check`](ChipSetBuilder::with_data_execution_check).
+    pub fn data_executions(&self) -> &[usize] {
+        &self.chipset.data_executions
+    }
+
+    /// Will show everything drawn since the last call, if the display is
+    /// [double buffered](ChipSetBuilder::with_double_buffering), otherwise this does
+    /// nothing.
+    pub fn present(&mut self) {
+        self.chipset.present();
+    }
+
+    /// Will return the last program counter values from the oldest to the newest, this
+    /// includes the program counter of the opcode that failed last.
+    /// The history is empty, if it was not enabled with
+    /// [`with_pc_history`](ChipSetBuilder::with_pc_history).
+    pub fn pc_history(&self) -> &[usize] {
+        &self.chipset.pc_history
+    }
+
+    /// Will return a view of the display.
+    pub fn frame(&self) -> Frame<'_> {
+        Frame::new(self.get_display())
+    }
+
     /// Will execute the next operation.
     /// Returns the operation that has to be run by the caller.
+    ///
+    /// Fails with [`TimerFailure`](ProcessError::TimerFailure), once the worker of a timer
+    /// has stopped (e.g. because the sound callback panicked), instead of running on
+    /// with timers that never reach zero.
     pub fn step(&mut self) -> Result<opcode::Operation, ProcessError> {
+        if !self.delay_timer.is_alive() || !self.sound_timer.is_alive() {
+            return Err(ProcessError::TimerFailure);
+        }
         self.chipset.next()
     }
 
@@ -75,6 +352,16 @@ where
         self.chipset.set_key(key, to);
     }
 
+    /// Will return a copy of the data registers `V0` to `VF`.
+    pub fn registers_array(&self) -> [u8; REGISTER_SIZE] {
+        self.chipset.registers
+    }
+
+    /// Will replace the data registers `V0` to `VF`.
+    pub fn set_registers_array(&mut self, registers: [u8; REGISTER_SIZE]) {
+        self.chipset.registers = registers;
+    }
+
     /// Get a reference to the chip set's chipset.
     pub(super) fn chipset(&self) -> &InternalChipSet {
         &self.chipset
@@ -84,7 +371,7 @@ where
     /// This function is only used in the context of tests
     /// as there never is a need to expose the internal
     /// chipset otherwise.
-    #[cfg(test)]
+    #[cfg(any(test, feature = "test-utils"))]
     pub(super) fn chipset_mut(&mut self) -> &mut InternalChipSet {
         &mut self.chipset
     }
@@ -94,10 +381,305 @@ where
         self.chipset.set_keyboard(keys);
     }
 
+    /// Will set all the keys at once from a bitmask, where bit `n` is key `n`, without
+    /// going through a slice.
+    pub fn set_keyboard_bitmask(&mut self, bitmask: u16) {
+        self.chipset.get_keyboard_write().set_bitmask(bitmask);
+    }
+
+    /// Checks if any key is pressed, keys disabled by the key mask are ignored.
+    pub fn any_key_pressed(&self) -> bool {
+        self.chipset.any_key_pressed()
+    }
+
+    /// Will set every key to the value returned by `f` for the key index. Unlike
+    /// [`set_keyboard`](Self::set_keyboard) the last changed key is tracked, so that a
+    /// waiting `FX0A` will notice the key press.
+    pub fn set_keys_from(&mut self, f: impl Fn(usize) -> bool) {
+        self.chipset.set_keys_from(f);
+    }
+
     /// will return the sound timer
     pub fn get_sound_timer(&self) -> u8 {
         self.chipset.get_sound_timer()
     }
+
+    /// will return the delay timer
+    pub fn get_delay_timer(&self) -> u8 {
+        self.chipset.get_delay_timer()
+    }
+
+    /// Will set the delay timer like `FX15`, without executing an opcode, e.g. to set up
+    /// timer dependent opcodes in tests or to scrub the value in a debugger. The timer
+    /// keeps counting down from the new value.
+    pub fn set_delay_timer(&mut self, value: u8) {
+        self.chipset.delay_timer.set_value(value);
+    }
+
+    /// Will set the sound timer like `FX18`, without executing an opcode, see
+    /// [`set_delay_timer`](Self::set_delay_timer). Starting or stopping a beep this way is
+    /// reported to the [`on_sound_change`](Self::on_sound_change) callback as well.
+    pub fn set_sound_timer(&mut self, value: u8) {
+        self.chipset.sound_timer.set_value(value);
+    }
+
+    /// Checks if the chip is currently playing a sound, which is the case while the
+    /// sound timer is counting down.
+    pub fn sound_active(&self) -> bool {
+        self.get_sound_timer() > 0
+    }
+
+    /// Will return the amount of cycles at [`HERTZ`](cpu::HERTZ) until the delay timer
+    /// reaches zero, computed from its current value and the frequency of the timers, so
+    /// that a host can predict when a timed event of the program fires. The result is
+    /// rounded up and only an estimate, as the timers run on their own.
+    pub fn cycles_until_delay_zero(&self) -> u64 {
+        self.cycles_until_zero(self.chipset.get_delay_timer())
+    }
+
+    /// Will return the amount of cycles at [`HERTZ`](cpu::HERTZ) until the sound timer
+    /// reaches zero, i.e. the current beep ends, see
+    /// [`cycles_until_delay_zero`](Self::cycles_until_delay_zero).
+    pub fn cycles_until_sound_zero(&self) -> u64 {
+        self.cycles_until_zero(self.chipset.get_sound_timer())
+    }
+
+    fn cycles_until_zero(&self, value: u8) -> u64 {
+        (value as u64 * cpu::HERTZ).div_ceil(self.timer_hz)
+    }
+
+    /// Will advance both the delay and the sound timer by up to `ticks` intervals at once,
+    /// stopping at zero, e.g. to fast-forward or to run deterministic tests with a
+    /// [`paused`](Self::pause) chip. Once the sound timer reaches zero, both the sound
+    /// callback and the [`on_sound_change`](Self::on_sound_change) callback are called
+    /// once.
+    pub fn tick_timers_by(&mut self, ticks: u32) {
+        // the timers hold a byte, so that more ticks all end at zero
+        let ticks = ticks.min(u8::MAX as u32) as u8;
+        self.delay_timer.tick_by(ticks);
+        self.sound_timer.tick_by(ticks);
+    }
+
+    /// Will set the callback, that is called with the new state every time
+    /// [`sound_active`](Self::sound_active) flips, replacing the previous one. The
+    /// callback is called from the thread of the timer, while the sound timer is locked,
+    /// so that it must not access the chip.
+    pub fn on_sound_change(&mut self, callback: ChangeCallback) {
+        self.sound_timer.on_change(Some(callback));
+    }
+
+    /// Will set every pixel of the display to the given value.
+    pub fn fill_display(&mut self, value: bool) {
+        self.chipset.fill_display(value);
+    }
+
+    /// Will blank the display without running `00E0`, e.g. for transitions of the user
+    /// interface. Unlike [`fill_display`](Self::fill_display) the front buffer of a
+    /// double buffered display and the collisions of the last draw are cleared as well.
+    pub fn clear_display(&mut self) {
+        let chip = &mut self.chipset;
+        chip.fill_display(false);
+        chip.present();
+        chip.last_collision.fill(false);
+    }
+
+    /// Will set the pixel at `(x, y)`, where `(0, 0)` is the upper left corner, so that a
+    /// known display can be set up before running an opcode. A double buffered display
+    /// only shows the pixel after it was [presented](Self::present).
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) -> Result<(), ProcessError> {
+        let pixel = self
+            .chipset
+            .display
+            .get_mut(y)
+            .and_then(|row| row.get_mut(x))
+            .ok_or(ProcessError::PixelOutOfBounds { x, y })?;
+        *pixel = on;
+        Ok(())
+    }
+
+    /// Will reset the chip to the state after the rom was loaded, with every pixel of the
+    /// display set to the given value. This is a cold reset, so that changes to the memory
+    /// are lost, see [`warm_reset`](Self::warm_reset) to keep them.
+    pub fn reset(&mut self, display: bool) {
+        self.chipset.reset(display);
+    }
+
+    /// Will restart the program like the reset button of the hardware. Unlike the cold
+    /// [`reset`](Self::reset) the memory, the RPL user flags and the display are kept, so
+    /// that data a program stored in the memory survives. Only the cpu is reset, i.e. the
+    /// program counter, the registers `V0` to `VF`, the index register, the stack and the
+    /// delay and sound timer, while an awaited key press or an exit are dropped.
+    pub fn warm_reset(&mut self) {
+        self.chipset.warm_reset();
+    }
+
+    /// Will return the amount of return addresses currently on the stack.
+    pub fn stack_depth(&self) -> usize {
+        self.chipset.stack_depth()
+    }
+
+    /// Will set the mask of the enabled keys, every cleared bit disables the
+    /// corresponding key, so that it always reads as not pressed.
+    pub fn set_key_mask(&mut self, mask: u16) {
+        self.chipset.set_key_mask(mask);
+    }
+
+    /// Will limit the amount of opcodes, that the chip may execute over its lifetime, so
+    /// that an untrusted rom can not run forever. Once the budget is exhausted every
+    /// step returns [`BudgetExceeded`](ProcessError::BudgetExceeded). Setting a new
+    /// budget replaces the remaining one, `None` removes the limit, which is the default.
+    pub fn set_instruction_budget(&mut self, budget: Option<u64>) {
+        self.chipset.instruction_budget = budget;
+    }
+
+    /// Will return the amount of opcodes, that may still be executed, or `None` if the
+    /// execution is unlimited.
+    pub fn instruction_budget(&self) -> Option<u64> {
+        self.chipset.instruction_budget
+    }
+
+    /// Will return the behaviours of the emulated interpreter, as currently active, so
+    /// that e.g. a settings panel can reflect changes made with
+    /// [`set_quirks`](Self::set_quirks).
+    pub fn quirks(&self) -> &Quirks {
+        &self.chipset.quirks
+    }
+
+    /// Will replace the behaviours of the emulated interpreter, while the program keeps
+    /// running, so that the effect of a quirk can be compared directly. Changing the
+    /// quirks in the middle of a frame can cause glitches (e.g. a partially drawn
+    /// sprite) until the program redraws the display.
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.chipset.quirks = quirks;
+        // the cached opcodes were checked against the previous mode
+        self.chipset.opcode_memory.clear();
+    }
+
+    /// Will return the RPL user flags, that are written by `FX75` in the
+    /// [`SChip`](Mode::SChip) mode, so that the host can persist them.
+    pub fn get_rpl_flags(&self) -> [u8; cpu::RPL_FLAGS] {
+        self.chipset.rpl_flags
+    }
+
+    /// Will set the RPL user flags, that are read by `FX85` in the
+    /// [`SChip`](Mode::SChip) mode, e.g. after loading them from disk.
+    pub fn set_rpl_flags(&mut self, flags: [u8; cpu::RPL_FLAGS]) {
+        self.chipset.rpl_flags = flags;
+    }
+
+    /// Will set the callback, that is called with the address `NNN` of every `0NNN`
+    /// machine code call, if the [`sys_call`](Quirks::sys_call) quirk is set to
+    /// [`Callback`](SysCall::Callback). The previous callback is replaced.
+    pub fn on_sys_call(&mut self, callback: impl FnMut(usize) + Send + 'static) {
+        self.chipset.on_sys_call = Some(Box::new(callback));
+    }
+
+    /// Will set the callback, that is called with every unsupported opcode before the
+    /// step returns the error, replacing the previous one. This allows to collect which
+    /// opcodes are needed by real roms. By default there is no callback.
+    pub fn on_unsupported(&mut self, callback: impl FnMut(Opcode) + Send + 'static) {
+        self.chipset.on_unsupported = Some(Box::new(callback));
+    }
+
+    /// Will set the filter, that transforms the bitmask of the keyboard (bit `n` is key
+    /// `n`) at the start of every executed opcode, replacing the previous one. The opcodes
+    /// read the returned bitmask instead of the keyboard, e.g. to implement autofire or to
+    /// remap keys without changing the input of the host. The key mask is applied after
+    /// the filter. By default there is no filter, so that the keyboard is read unchanged.
+    pub fn set_input_filter(&mut self, filter: impl FnMut(u16) -> u16 + Send + 'static) {
+        self.chipset.input_filter = Some(Box::new(filter));
+    }
+
+    /// Will remove the input filter, so that the keyboard is read unchanged again.
+    pub fn clear_input_filter(&mut self) {
+        let chip = &mut self.chipset;
+        chip.input_filter = None;
+        chip.filtered_keys = None;
+    }
+
+    /// Will set the callback, that is called once for every `DXYN`, which turned off a
+    /// pixel (i.e. set `VF` to `1`), replacing the previous one. This allows the host to
+    /// signal collisions independently of the sound timer, e.g. with a rumble motor. By
+    /// default there is no callback.
+    pub fn on_collision(&mut self, callback: impl FnMut() + Send + 'static) {
+        self.chipset.on_collision = Some(Box::new(callback));
+    }
+
+    /// Will return the amount of bytes of the loaded rom at and after the program counter,
+    /// so that a frontend can show the position of the program counter within the
+    /// program. The result is clamped, so that it is the size of the rom while the
+    /// program counter is before the program and `0` once it is past its end.
+    pub fn remaining_program_bytes(&self) -> usize {
+        let chip = &self.chipset;
+        let end = cpu::PROGRAM_COUNTER + chip.rom.len();
+        end.saturating_sub(chip.program_counter.max(cpu::PROGRAM_COUNTER))
+    }
+
+    /// Will set the callback, that is called with the addresses `(from, to)` every time a
+    /// `1NNN`, `BNNN` or `00EE` moves the program counter to a lower address, replacing
+    /// the previous one. Such jumps usually close a loop (e.g. the main loop of a game), so
+    /// that tools can find the loop boundaries or the start of a new frame. By default
+    /// there is no callback.
+    pub fn on_backward_jump(&mut self, callback: impl FnMut(usize, usize) + Send + 'static) {
+        self.chipset.on_backward_jump = Some(Box::new(callback));
+    }
+
+    /// Will return a checksum of the memory, that is updated with every write of the chip
+    /// instead of hashing the whole memory, so that a change of the code can be detected
+    /// cheaply every frame. Equal memory contents always have the same checksum.
+    pub fn memory_checksum(&self) -> u64 {
+        self.chipset.memory_checksum
+    }
+
+    /// Checks if the program has ended, so that the host can stop the emulation. A
+    /// program counts as halted, once
+    /// - it exited the interpreter with the SuperChip `00FD` opcode, until the chip is
+    ///   reset, or
+    /// - while it is stuck in an idle loop, i.e. the opcode at the program counter is a
+    ///   `1NNN` jump onto itself.
+    ///
+    /// Loops that wait for a key or a timer are not detected, as they can still make
+    /// progress.
+    pub fn is_halted(&self) -> bool {
+        self.chipset.halted
+            || matches!(
+                self.current_instruction(),
+                Ok(Opcodes::One(opcode::One { nnn })) if nnn == self.chipset.program_counter
+            )
+    }
+
+    /// Checks if the chip is paused.
+    pub fn is_paused(&self) -> bool {
+        self.delay_timer.is_paused()
+    }
+
+    /// Will pause the chip, so that the [`run`](crate::run) and
+    /// [`run_until`](Self::run_until) functions return without executing anything.
+    /// The delay and sound timer will not count down while paused.
+    ///
+    /// Single opcodes can still be executed with [`step`](Self::step), so that a
+    /// paused chip can be debugged.
+    pub fn pause(&mut self) {
+        self.set_paused(true);
+    }
+
+    /// Will resume the paused chip.
+    pub fn resume(&mut self) {
+        self.set_paused(false);
+    }
+
+    /// Will pause a running chip or resume a paused one.
+    /// Returns if the chip is paused now.
+    pub fn toggle_pause(&mut self) -> bool {
+        let paused = !self.is_paused();
+        self.set_paused(paused);
+        paused
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.delay_timer.set_paused(paused);
+        self.sound_timer.set_paused(paused);
+    }
 }
 
 /// The ChipSet struct represents the current state
@@ -105,8 +687,8 @@ where
 /// needed for emulating an instant on the
 /// Chip8 CPU.
 pub(super) struct InternalChipSet {
-    /// name of the loaded rom
-    pub(super) name: String,
+    /// the loaded rom
+    pub(super) rom: Rom,
     /// - `0x000-0x1FF` - Chip 8 interpreter (contains font set in emu)
     /// - `0x050-0x0A0` - Used for the built in `4x5` pixel font set (`0-F`)
     /// - `0x200-0xFFF` - Program ROM and work RAM
@@ -143,12 +725,32 @@ pub(super) struct InternalChipSet {
     /// The graphics of the Chip 8 are black and white and the screen has a total of `2048` pixels
     /// `(64 x 32)`. This can easily be implemented using an array that hold the pixel state `(1 or 0)`:
     pub(super) display: Vec<Vec<bool>>,
+    /// The display shown to the host, if the display is double buffered. The opcodes
+    /// will then draw into the `display` as the back buffer.
+    pub(super) front_display: Option<Vec<Vec<bool>>>,
+    /// The pixels turned off by a collision during the last draw, row by row.
+    pub(super) last_collision: Vec<bool>,
+    /// The `(x, y)` coordinates of the pixels toggled by the last draw, if recorded.
+    pub(super) toggled_pixels: Option<Vec<(usize, usize)>>,
+    /// Marks every byte of the memory, that was read as sprite data, if the data
+    /// execution check is enabled.
+    pub(super) sprite_reads: Option<Vec<bool>>,
+    /// The addresses at which sprite data was executed.
+    pub(super) data_executions: Vec<usize>,
     /// Input is done with a hex keyboard that has 16 keys ranging `0-F`. The `8`, `4`, `6`, and
     /// `2` keys are typically used for directional input. Three opcodes are used to detect input.
     /// One skips an instruction if a specific key is pressed, while another does the same if a
     /// specific key is not pressed. The third waits for a key press, and then stores it in one of
     /// the data registers.
     pub(super) keyboard: Arc<RwLock<Keyboard>>,
+    /// The mask of the enabled keys, where bit `n` represents key `n`. Disabled keys
+    /// will always read as not pressed, so that unconnected inputs of partial keypads
+    /// can be ignored.
+    pub(super) key_mask: u16,
+    /// Transforms the bitmask of the keyboard, before it is read by an opcode.
+    pub(super) input_filter: Option<Box<dyn FnMut(u16) -> u16 + Send>>,
+    /// The bitmask returned by the input filter at the start of the current opcode.
+    pub(super) filtered_keys: Option<u16>,
     /// This stores the random number generator, used by the chipset.
     /// It is stored into the chipset, so as to enable simple mocking
     /// of the given type.
@@ -156,7 +758,63 @@ pub(super) struct InternalChipSet {
     /// Will store the callbacks needed for certain tasks
     /// example, running special code after the main caller
     /// did his. (Do work after wait etc.)
-    pub(super) preprocessor: Option<Box<dyn FnOnce(&mut Self) + Send>>,
+    pub(super) preprocessor: Option<PreProcessor>,
+    /// If sprites are drawn row by row.
+    pub(super) slow_draw: bool,
+    /// The sprite, that is currently drawn row by row.
+    pub(super) pending_draw: Option<PendingDraw>,
+    /// The last program counter values, the oldest value first.
+    pub(super) pc_history: Vec<usize>,
+    /// The maximum amount of values retained in the `pc_history`.
+    pub(super) pc_history_capacity: usize,
+    /// The amount of executed opcodes.
+    pub(super) cycles: usize,
+    /// The amount of cycles a key polled as pressed is suppressed for, `0` disables it.
+    pub(super) key_debounce_cycles: usize,
+    /// The cycle at which each key was last polled as pressed.
+    pub(super) key_polls: [Option<usize>; keyboard::SIZE],
+    /// The amount of opcodes, that may still be executed, `None` is unlimited.
+    pub(super) instruction_budget: Option<u64>,
+    /// Is called with every unsupported opcode, that the chip tries to execute.
+    pub(super) on_unsupported: Option<Box<dyn FnMut(Opcode) + Send>>,
+    /// Is called after every draw, that caused a collision.
+    pub(super) on_collision: Option<Box<dyn FnMut() + Send>>,
+    /// Is called with the addresses of every jump or return to a lower address.
+    pub(super) on_backward_jump: Option<Box<dyn FnMut(usize, usize) + Send>>,
+    /// The behaviours of the emulated interpreter.
+    pub(super) quirks: Quirks,
+    /// The amount of data registers addressable by the `X` and `Y` operands.
+    pub(super) register_count: usize,
+    /// Is called with the address of every `0NNN` machine code call, if enabled by the
+    /// quirks.
+    pub(super) on_sys_call: Option<Box<dyn FnMut(usize) + Send>>,
+    /// The RPL user flags of the SuperChip, which are kept over a reset.
+    pub(super) rpl_flags: [u8; cpu::RPL_FLAGS],
+    /// If the program exited the interpreter with `00FD`.
+    pub(super) halted: bool,
+    /// The memory addresses, at which a write is reported.
+    pub(super) write_watches: Vec<usize>,
+    /// The watched address written by the last opcode.
+    pub(super) write_watch_hit: Option<usize>,
+    /// The channels receiving the events of the chip.
+    pub(super) subscribers: super::events::Subscribers,
+    /// The checksum of the memory, that is updated on every write.
+    pub(super) memory_checksum: u64,
+    /// The hooks called around the execution of every opcode.
+    #[cfg(feature = "instrument")]
+    pub(super) hooks: super::instrument::Hooks,
+    /// The patterns of all the executed opcodes.
+    #[cfg(feature = "metrics")]
+    pub(super) executed_opcode_kinds: std::collections::HashSet<&'static str>,
+    /// The counters of the executed opcodes.
+    #[cfg(feature = "metrics")]
+    pub(super) stats: super::Stats,
+    /// The amount of executions of every address.
+    #[cfg(feature = "metrics")]
+    pub(super) execution_profile: std::collections::HashMap<usize, u64>,
+    /// The memory writes of the opcodes, that were not taken yet.
+    #[cfg(feature = "trace")]
+    pub(super) write_log: Vec<super::MemWrite>,
 }
 
 impl InternalChipSet {
@@ -167,22 +825,11 @@ impl InternalChipSet {
         sound_timer: TimerValue<u8>,
         keyboard: Arc<RwLock<Keyboard>>,
     ) -> Self {
-        // initialize all the memory with 0
-
-        let mut ram = vec![0; memory::SIZE];
-
-        // load fonts
-        ram[display::fontset::LOCATION
-            ..(display::fontset::LOCATION + display::fontset::FONTSET.len())]
-            .copy_from_slice(&display::fontset::FONTSET);
-
-        // write the rom data into memory
-        let data = rom.get_data();
-        ram[cpu::PROGRAM_COUNTER..(cpu::PROGRAM_COUNTER + rom.get_data().len())]
-            .copy_from_slice(data);
+        let ram = Self::init_memory(&rom);
 
         Self {
-            name: rom.get_name().to_string(),
+            rom,
+            memory_checksum: memory_checksum(&ram),
             memory: ram,
             opcode_memory: HashMap::new(),
             registers: [0; cpu::register::SIZE],
@@ -192,18 +839,214 @@ impl InternalChipSet {
             delay_timer,
             sound_timer,
             display: vec![vec![false; display::HEIGHT]; display::WIDTH],
+            front_display: None,
+            last_collision: vec![false; display::RESOLUTION],
+            toggled_pixels: None,
+            sprite_reads: None,
+            data_executions: Vec::new(),
             keyboard,
+            key_mask: u16::MAX,
+            input_filter: None,
+            filtered_keys: None,
             rng: Box::new(rand::rngs::OsRng {}),
             preprocessor: None,
+            slow_draw: false,
+            pending_draw: None,
+            pc_history: Vec::new(),
+            pc_history_capacity: 0,
+            cycles: 0,
+            key_debounce_cycles: 0,
+            key_polls: [None; keyboard::SIZE],
+            instruction_budget: None,
+            on_unsupported: None,
+            on_collision: None,
+            on_backward_jump: None,
+            quirks: Quirks::default(),
+            register_count: cpu::register::SIZE,
+            on_sys_call: None,
+            rpl_flags: [0; cpu::RPL_FLAGS],
+            halted: false,
+            write_watches: Vec::new(),
+            write_watch_hit: None,
+            subscribers: Default::default(),
+            #[cfg(feature = "instrument")]
+            hooks: Default::default(),
+            #[cfg(feature = "metrics")]
+            executed_opcode_kinds: Default::default(),
+            #[cfg(feature = "metrics")]
+            stats: Default::default(),
+            #[cfg(feature = "metrics")]
+            execution_profile: Default::default(),
+            #[cfg(feature = "trace")]
+            write_log: Vec::new(),
         }
     }
 
+    /// Will create the memory containing the fontset and the rom data.
+    fn init_memory(rom: &Rom) -> Vec<u8> {
+        // initialize all the memory with 0
+        let mut ram = vec![0; memory::SIZE];
+
+        // load fonts
+        ram[display::fontset::LOCATION
+            ..(display::fontset::LOCATION + display::fontset::FONTSET.len())]
+            .copy_from_slice(&display::fontset::FONTSET);
+
+        // write the rom data into memory
+        ram[cpu::PROGRAM_COUNTER..(cpu::PROGRAM_COUNTER + rom.len())]
+            .copy_from_slice(rom.get_data());
+
+        ram
+    }
+
+    /// Will reset the chipset to the state after the rom was loaded, with every pixel of the
+    /// display set to the given value. The key mask, the instruction budget and the RPL
+    /// user flags are kept.
+    pub fn reset(&mut self, display: bool) {
+        self.set_memory(Self::init_memory(&self.rom));
+        self.opcode_memory.clear();
+        self.registers = [0; cpu::register::SIZE];
+        self.index_register = 0;
+        self.program_counter = cpu::PROGRAM_COUNTER;
+        self.stack.clear();
+        self.delay_timer.set_value(0);
+        self.sound_timer.set_value(0);
+        self.fill_display(display);
+        self.present();
+        self.last_collision.fill(false);
+        if let Some(toggled) = self.toggled_pixels.as_mut() {
+            toggled.clear();
+        }
+        if let Some(reads) = self.sprite_reads.as_mut() {
+            reads.fill(false);
+        }
+        self.data_executions.clear();
+        self.preprocessor = None;
+        self.pending_draw = None;
+        self.pc_history.clear();
+        self.cycles = 0;
+        self.key_polls = [None; keyboard::SIZE];
+        self.halted = false;
+        #[cfg(feature = "metrics")]
+        {
+            self.executed_opcode_kinds.clear();
+            self.stats = Default::default();
+            self.execution_profile.clear();
+        }
+        #[cfg(feature = "trace")]
+        self.write_log.clear();
+    }
+
+    /// Will reset the cpu, while keeping the memory, see
+    /// [`ChipSet::warm_reset`](ChipSet::warm_reset).
+    pub fn warm_reset(&mut self) {
+        self.registers = [0; cpu::register::SIZE];
+        self.index_register = 0;
+        self.program_counter = cpu::PROGRAM_COUNTER;
+        self.stack.clear();
+        self.delay_timer.set_value(0);
+        self.sound_timer.set_value(0);
+        self.preprocessor = None;
+        self.pending_draw = None;
+        self.halted = false;
+    }
+
+    /// Will replace the whole memory.
+    pub(super) fn set_memory(&mut self, memory: Vec<u8>) {
+        self.memory_checksum = memory_checksum(&memory);
+        self.memory = memory;
+    }
+
+    /// Will write the data into the memory starting at the given address, keeping the
+    /// checksum up to date.
+    pub(super) fn write_memory(&mut self, address: usize, data: &[u8]) {
+        let memory = &mut self.memory[address..(address + data.len())];
+        for (offset, (byte, &value)) in memory.iter_mut().zip(data).enumerate() {
+            self.memory_checksum ^=
+                memory_hash(address + offset, *byte) ^ memory_hash(address + offset, value);
+            *byte = value;
+        }
+    }
+
+    /// Will resolve an address of an opcode accessing the memory relative to `I`, which
+    /// might lie past the end of the memory, according to the
+    /// [`memory_bounds`](Quirks::memory_bounds) quirk.
+    fn bounded_address(&self, address: usize) -> Result<usize, ProcessError> {
+        if address < memory::SIZE {
+            return Ok(address);
+        }
+        match self.quirks.memory_bounds {
+            MemoryBounds::Error => Err(ProcessError::MemoryOutOfBounds(address)),
+            MemoryBounds::Wrap => Ok(address % memory::SIZE),
+            MemoryBounds::Clamp => Ok(memory::SIZE - 1),
+        }
+    }
+
+    /// Will record a write of an opcode to a watched address.
+    fn watch_writes(&mut self, mut addresses: impl Iterator<Item = usize>) {
+        if self.write_watches.is_empty() {
+            return;
+        }
+        if let Some(watch) = addresses.find(|address| self.write_watches.contains(address)) {
+            self.write_watch_hit = Some(watch);
+            self.subscribers.emit(Event::Breakpoint { addr: watch });
+        }
+    }
+
+    /// Will write the data of an opcode like [`write_memory`](Self::write_memory), but the
+    /// addresses past the end of the memory are resolved by the memory bounds quirk and a
+    /// write to a watched address is recorded. On an error nothing is written.
+    pub(super) fn write_memory_bounded(
+        &mut self,
+        address: usize,
+        data: &[u8],
+    ) -> Result<(), ProcessError> {
+        if address + data.len() <= memory::SIZE {
+            #[cfg(feature = "trace")]
+            self.trace_writes(address..(address + data.len()), data);
+            self.watch_writes(address..(address + data.len()));
+            self.write_memory(address, data);
+            return Ok(());
+        }
+        let addresses = (address..(address + data.len()))
+            .map(|address| self.bounded_address(address))
+            .collect::<Result<Vec<_>, _>>()?;
+        #[cfg(feature = "trace")]
+        self.trace_writes(addresses.iter().copied(), data);
+        self.watch_writes(addresses.iter().copied());
+        for (address, &value) in addresses.into_iter().zip(data) {
+            self.write_memory(address, &[value]);
+        }
+        Ok(())
+    }
+
+    /// Will fill the buffer with the memory starting at the address, where the addresses
+    /// past the end of the memory are resolved by the memory bounds quirk.
+    pub(super) fn read_memory_bounded(
+        &self,
+        address: usize,
+        buffer: &mut [u8],
+    ) -> Result<(), ProcessError> {
+        if address + buffer.len() <= memory::SIZE {
+            buffer.copy_from_slice(&self.memory[address..(address + buffer.len())]);
+            return Ok(());
+        }
+        for (offset, value) in buffer.iter_mut().enumerate() {
+            *value = self.memory[self.bounded_address(address + offset)?];
+        }
+        Ok(())
+    }
+
     /// Will get the next opcode from memory
     pub fn get_opcode(&mut self) -> Result<Opcodes, OpcodeError> {
         // Sadly we have to use copy here, given the borrow mut later on
         let iops = match self.opcode_memory.get(&self.program_counter) {
             None => {
-                let iops = opcode::build_opcode(&self.memory, self.program_counter)?.try_into()?;
+                let value = opcode::build_opcode(&self.memory, self.program_counter)?;
+                let iops = value.try_into()?;
+                if !self.quirks.mode.supports(&iops) {
+                    return Err(OpcodeError::InvalidOpcode(value));
+                }
                 self.opcode_memory.insert(self.program_counter, iops);
                 iops
             }
@@ -217,23 +1060,145 @@ impl InternalChipSet {
     pub fn next(&mut self) -> Result<opcode::Operation, ProcessError> {
         // import here as to not bloat the namespace
         use crate::opcode::ChipOpcodes;
-        // get next opcode
-        let opcode = self.get_opcode()?;
+        // an exited program does not continue
+        if self.halted {
+            return Ok(opcode::Operation::Exit);
+        }
+        self.filter_keys();
+        if let Some(budget) = self.instruction_budget.as_mut() {
+            if *budget == 0 {
+                return Err(ProcessError::BudgetExceeded);
+            }
+            *budget -= 1;
+        }
+        self.record_pc();
+        self.check_data_execution();
+        self.cycles += 1;
+        // get next opcode, reading past the memory is reported separately from
+        // an unsupported opcode
+        let opcode = match self.get_opcode() {
+            Err(OpcodeError::InvalidOpcode(value))
+                if value & 0xF000 == 0 && self.quirks.sys_call != SysCall::Error =>
+            {
+                return Ok(self.sys_call(value));
+            }
+            opcode => opcode,
+        };
+        let opcode = opcode.map_err(|err| match err {
+            OpcodeError::MemoryInvalid { .. } => ProcessError::OpcodeFetch(err),
+            OpcodeError::InvalidOpcode(opcode) => {
+                if let Some(callback) = self.on_unsupported.as_mut() {
+                    callback(opcode);
+                }
+                err.into()
+            }
+        })?;
+        // a fetch past the memory or of an unsupported opcode is reported first
+        if self.quirks.enforce_alignment
+            && !self.program_counter.is_multiple_of(memory::opcodes::SIZE)
+        {
+            return Err(ProcessError::MisalignedProgramCounter(self.program_counter));
+        }
+        // the operands are masked to a nibble, so that this only fails for a reduced bank
+        if let Some(register) = opcode
+            .highest_register()
+            .filter(|&register| register >= self.register_count)
+        {
+            return Err(ProcessError::InvalidRegister(register));
+        }
+        #[cfg(feature = "metrics")]
+        {
+            self.record_opcode_kind();
+            self.stats.record(&opcode);
+            *self
+                .execution_profile
+                .entry(self.program_counter)
+                .or_insert(0) += 1;
+        }
         // run the opcode
-        self.calc(&opcode)
+        let from = self.program_counter;
+        let operation = self.calc(&opcode)?;
+        if let opcode::Operation::Draw
+        | opcode::Operation::Clear
+        | opcode::Operation::Scroll { .. } = operation
+        {
+            self.subscribers.emit(Event::Draw);
+        }
+        if let Opcodes::One(_) | Opcodes::B(_) | Opcodes::Zero(opcode::Zero::Return) = opcode {
+            self.report_backward_jump(from);
+        }
+        Ok(operation)
+    }
+
+    /// Will pass the jump from the given address to the callback, if it moved the program
+    /// counter to a lower address.
+    fn report_backward_jump(&mut self, from: usize) {
+        let to = self.program_counter;
+        if to < from {
+            if let Some(callback) = self.on_backward_jump.as_mut() {
+                callback(from, to);
+            }
+        }
+    }
+
+    /// Will record the pattern of the opcode at the program counter as executed.
+    #[cfg(feature = "metrics")]
+    fn record_opcode_kind(&mut self) {
+        let info = opcode::build_opcode(&self.memory, self.program_counter)
+            .ok()
+            .and_then(|value| opcode::opcode_info(value, self.quirks.mode));
+        if let Some(info) = info {
+            self.executed_opcode_kinds.insert(info.pattern);
+        }
+    }
+
+    /// Will skip the `0NNN` machine code call, after passing it to the callback if
+    /// configured so.
+    fn sys_call(&mut self, value: opcode::Opcode) -> opcode::Operation {
+        if self.quirks.sys_call == SysCall::Callback {
+            if let Some(callback) = self.on_sys_call.as_mut() {
+                callback((value & 0x0FFF) as usize);
+            }
+        }
+        self.step(ProgramCounterStep::Next);
+        opcode::Operation::None
+    }
+
+    /// Will add the current program counter to the history, dropping the oldest value
+    /// once the capacity is reached.
+    fn record_pc(&mut self) {
+        if self.pc_history_capacity == 0 {
+            return;
+        }
+        if self.pc_history.len() == self.pc_history_capacity {
+            self.pc_history.remove(0);
+        }
+        self.pc_history.push(self.program_counter);
+    }
+
+    /// Will warn once the program counter enters memory that was read as sprite data.
+    fn check_data_execution(&mut self) {
+        let pc = self.program_counter;
+        let is_data = match self.sprite_reads.as_ref() {
+            Some(reads) => reads.get(pc).copied().unwrap_or(false),
+            None => return,
+        };
+        if is_data && !self.data_executions.contains(&pc) {
+            log::warn!("Executing sprite data as opcode at {:#06X}", pc);
+            self.data_executions.push(pc);
+        }
     }
 
-    pub(super) fn get_keyboard_write(&mut self) -> RwLockWriteGuard<Keyboard> {
+    pub(super) fn get_keyboard_write(&mut self) -> RwLockWriteGuard<'_, Keyboard> {
         self.keyboard.write()
     }
 
-    pub(super) fn get_keyboard_read(&self) -> RwLockReadGuard<Keyboard> {
+    pub(super) fn get_keyboard_read(&self) -> RwLockReadGuard<'_, Keyboard> {
         self.keyboard.read()
     }
 
     /// Will write keyboard data into interncal keyboard representation.
     pub fn set_keyboard(&mut self, keys: &[bool; keyboard::SIZE]) {
-        // copy_from_slice checks the keys lenght during copy
         self.get_keyboard_write().set_mult(keys);
     }
 
@@ -242,6 +1207,73 @@ impl InternalChipSet {
         self.get_keyboard_write().set_key(key, to)
     }
 
+    /// Will set the value of every key to the value returned by `f`.
+    pub fn set_keys_from(&mut self, f: impl Fn(usize) -> bool) {
+        // the keys are computed before locking the keyboard, which `f` might read
+        let mut keys = [false; keyboard::SIZE];
+        for (key, pressed) in keys.iter_mut().enumerate() {
+            *pressed = f(key);
+        }
+        let mut keyboard = self.get_keyboard_write();
+        for (key, &pressed) in keys.iter().enumerate() {
+            keyboard.set_key(key, pressed);
+        }
+    }
+
+    /// Will set the mask of the enabled keys.
+    pub fn set_key_mask(&mut self, mask: u16) {
+        self.key_mask = mask;
+    }
+
+    /// Will check if the given key is enabled by the key mask, a key past the keyboard
+    /// (e.g. taken from a register) is never enabled.
+    pub fn is_key_enabled(&self, key: usize) -> bool {
+        key < keyboard::SIZE && self.key_mask & (1 << key) != 0
+    }
+
+    /// Will check if the given key is pressed, respecting the key mask.
+    pub fn is_key_pressed(&self, key: usize) -> bool {
+        self.is_key_enabled(key) && self.key_bitmask() & (1 << key) != 0
+    }
+
+    /// Will return the bitmask of the pressed keys as seen by the opcodes, i.e. after the
+    /// input filter.
+    pub(super) fn key_bitmask(&self) -> u16 {
+        self.filtered_keys
+            .unwrap_or_else(|| self.get_keyboard_read().bitmask())
+    }
+
+    /// Will run the input filter over the current state of the keyboard.
+    fn filter_keys(&mut self) {
+        if let Some(filter) = self.input_filter.as_mut() {
+            let keys = self.keyboard.read().bitmask();
+            self.filtered_keys = Some(filter(keys));
+        }
+    }
+
+    /// Will check if the given key is pressed like [`is_key_pressed`](Self::is_key_pressed),
+    /// but a held key will only read as pressed once within the debounce window.
+    pub(super) fn poll_key(&mut self, key: usize) -> bool {
+        if !self.is_key_pressed(key) {
+            return false;
+        }
+        if self.key_debounce_cycles == 0 {
+            return true;
+        }
+        match self.key_polls[key] {
+            Some(cycle) if self.cycles - cycle < self.key_debounce_cycles => false,
+            _ => {
+                self.key_polls[key] = Some(self.cycles);
+                true
+            }
+        }
+    }
+
+    /// Checks if any key is pressed, respecting the key mask.
+    pub fn any_key_pressed(&self) -> bool {
+        self.key_bitmask() & self.key_mask != 0
+    }
+
     /// will return the sound timer
     pub fn get_sound_timer(&self) -> u8 {
         self.sound_timer.get_value()
@@ -254,7 +1286,26 @@ impl InternalChipSet {
 
     /// Will return a immutable slice of the current display configuration
     pub fn get_display(&self) -> &[Vec<bool>] {
-        &self.display[..]
+        self.front_display.as_ref().unwrap_or(&self.display)
+    }
+
+    /// Will copy the back buffer into the front buffer, if the display is double buffered.
+    pub fn present(&mut self) {
+        if let Some(front) = self.front_display.as_mut() {
+            front.clone_from(&self.display);
+        }
+    }
+
+    /// Will set every pixel of the display to the given value.
+    pub fn fill_display(&mut self, value: bool) {
+        for row in self.display.iter_mut() {
+            row.fill(value);
+        }
+    }
+
+    /// Will return the amount of return addresses currently on the stack.
+    pub fn stack_depth(&self) -> usize {
+        self.stack.len()
     }
 
     /// Will push the current pointer to the stack
@@ -283,6 +1334,26 @@ impl InternalChipSet {
     }
 }
 
+/// Will hash a single byte of the memory together with its address, so that the
+/// checksum of the memory can be updated by xoring out the old and in the new value.
+fn memory_hash(address: usize, value: u8) -> u64 {
+    // the finalizer of splitmix64
+    let mut hash = ((address as u64) << 8 | value as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^ (hash >> 31)
+}
+
+/// Will calculate the checksum of the whole memory.
+fn memory_checksum(memory: &[u8]) -> u64 {
+    memory
+        .iter()
+        .enumerate()
+        .fold(0, |checksum, (address, &value)| {
+            checksum ^ memory_hash(address, value)
+        })
+}
+
 impl ProgramCounter for InternalChipSet {
     fn step(&mut self, step: ProgramCounterStep) {
         self.program_counter = if let ProgramCounterStep::Jump(_) = step {
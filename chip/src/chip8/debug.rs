@@ -0,0 +1,296 @@
+//! The debugging helpers written for the [`chipset`](super::ChipSet).
+//! This implementation was split up into this file for smaller file sizes and higher
+//! cohesion.
+
+use super::{ChipSet, Frame};
+use crate::{
+    definitions::{cpu, memory},
+    opcode::{self, Opcode, Opcodes, Operation},
+    resources,
+    timer::{TimedWorker, TimerCallback},
+    OpcodeError, ProcessError,
+};
+use std::convert::TryInto;
+use web_time::Instant;
+
+/// The reason why a debugging run stopped executing.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RunStop {
+    /// The subroutine, that was running at the start, returned to its caller.
+    Returned,
+    /// A single opcode, that was not a subroutine call, was executed.
+    Stepped,
+    /// The chip is waiting for a key press, so that no further progress can be made.
+    Wait,
+    /// The maximum amount of executed opcodes was reached.
+    CycleLimit,
+    /// The deadline passed before all opcodes could be executed.
+    Deadline,
+    /// The chip is paused, so that nothing was executed.
+    Paused,
+    /// An opcode wrote to a watched memory address.
+    WriteWatch {
+        /// The watched address, that was written.
+        addr: usize,
+    },
+}
+
+/// Everything that happened during a single step, see
+/// [`debug_step`](ChipSet::debug_step).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StepReport {
+    /// The raw opcode that was executed.
+    pub opcode: Opcode,
+    /// The decoded opcode that was executed, which is `None` for a `0NNN` machine code
+    /// call, that was skipped as configured by the [`quirks`](crate::quirks::SysCall).
+    pub instruction: Option<Opcodes>,
+    /// The operation that has to be run by the caller.
+    pub operation: Operation,
+    /// The program counter after the step.
+    pub program_counter: usize,
+    /// The data registers `V0` to `VF` after the step.
+    pub registers: [u8; cpu::register::SIZE],
+    /// The value of the delay timer after the step.
+    pub delay_timer: u8,
+    /// The value of the sound timer after the step.
+    pub sound_timer: u8,
+}
+
+impl<W, S> ChipSet<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    /// Will return the raw opcode at the program counter, that will be executed next.
+    /// The chip is not modified.
+    pub fn current_opcode(&self) -> Result<Opcode, OpcodeError> {
+        let chip = self.chipset();
+        opcode::build_opcode(&chip.memory, chip.program_counter)
+    }
+
+    /// Will decode the opcode at the program counter, that will be executed next.
+    /// The chip is not modified.
+    pub fn current_instruction(&self) -> Result<Opcodes, OpcodeError> {
+        self.current_opcode()?.try_into()
+    }
+
+    /// Will decode the opcode stored at the given address together with its mnemonic, as
+    /// it is listed by the [`disassembly`](crate::resources::Rom::disassembly), e.g.
+    /// `DRW V1, V2, 5`. The chip is not modified.
+    ///
+    /// Fails with [`MemoryInvalid`](OpcodeError::MemoryInvalid), if the address is past
+    /// the memory, and with [`InvalidOpcode`](OpcodeError::InvalidOpcode) for words, that
+    /// are not supported in the mode of the [`quirks`](Self::quirks). An instruction list
+    /// can show the latter as a data word like the disassembly, e.g. `DW 0x0123`.
+    pub fn instruction_at(&self, addr: usize) -> Result<(Opcodes, String), OpcodeError> {
+        let chip = self.chipset();
+        let value = opcode::build_opcode(&chip.memory, addr)?;
+        resources::format_opcode(value, chip.quirks.mode).ok_or(OpcodeError::InvalidOpcode(value))
+    }
+
+    /// Will execute the next opcode like [`step`](Self::step), but will report the
+    /// executed opcode and the resulting state as well.
+    pub fn debug_step(&mut self) -> Result<StepReport, ProcessError> {
+        let opcode = self.current_opcode().map_err(ProcessError::OpcodeFetch)?;
+        // stepping first, so that an unsupported opcode is reported to the callback
+        let operation = self.step()?;
+        // the step succeeded, so that only a handled `0NNN` sys call cannot be decoded
+        let instruction = opcode.try_into().ok();
+
+        let chip = self.chipset();
+        Ok(StepReport {
+            opcode,
+            instruction,
+            operation,
+            program_counter: chip.program_counter,
+            registers: chip.registers,
+            delay_timer: chip.get_delay_timer(),
+            sound_timer: chip.get_sound_timer(),
+        })
+    }
+
+    /// Will execute opcodes until the current subroutine returns to its caller (the
+    /// stack depth drops below the depth at the start), like the "step out" of a
+    /// debugger. At most `max` opcodes are executed.
+    ///
+    /// Attention if there is no subroutine running at the start, this will only stop
+    /// on a wait or after `max` opcodes.
+    pub fn run_until_return(&mut self, max: usize) -> Result<RunStop, ProcessError> {
+        let depth = self.stack_depth();
+
+        for _ in 0..max {
+            if self.step()? == Operation::Wait {
+                return Ok(RunStop::Wait);
+            }
+
+            if self.stack_depth() < depth {
+                return Ok(RunStop::Returned);
+            }
+        }
+
+        Ok(RunStop::CycleLimit)
+    }
+
+    /// Will execute the next opcode like the "step over" of a debugger: a `2NNN` call is
+    /// run together with the whole subroutine, until the program counter is back at the
+    /// opcode after the call, which returns [`Returned`](RunStop::Returned). At most `max`
+    /// opcodes are executed. Any other opcode is executed as a single step, which returns
+    /// [`Stepped`](RunStop::Stepped).
+    pub fn step_over(&mut self, max: usize) -> Result<RunStop, ProcessError> {
+        let target = match self.current_instruction() {
+            Ok(Opcodes::Two(_)) => self.chipset().program_counter + memory::opcodes::SIZE,
+            _ => {
+                return match self.step()? {
+                    Operation::Wait => Ok(RunStop::Wait),
+                    _ => Ok(RunStop::Stepped),
+                };
+            }
+        };
+        let depth = self.stack_depth();
+
+        for _ in 0..max {
+            if self.step()? == Operation::Wait {
+                return Ok(RunStop::Wait);
+            }
+
+            // a recursive call might pass the target with a deeper stack
+            if self.chipset().program_counter == target && self.stack_depth() <= depth {
+                return Ok(RunStop::Returned);
+            }
+        }
+
+        Ok(RunStop::CycleLimit)
+    }
+
+    /// Will execute opcodes until either the `deadline` passes or `max_cycles` opcodes
+    /// were executed, so that a host can cap the work done per frame.
+    ///
+    /// The deadline is checked before each opcode, so that a deadline in the past will
+    /// not execute anything. A paused chip will not execute anything either.
+    pub fn run_until(
+        &mut self,
+        deadline: Instant,
+        max_cycles: usize,
+    ) -> Result<RunStop, ProcessError> {
+        if self.is_paused() {
+            return Ok(RunStop::Paused);
+        }
+
+        for _ in 0..max_cycles {
+            if Instant::now() >= deadline {
+                return Ok(RunStop::Deadline);
+            }
+
+            if self.step()? == Operation::Wait {
+                return Ok(RunStop::Wait);
+            }
+        }
+
+        Ok(RunStop::CycleLimit)
+    }
+
+    /// Will watch the memory address, so that [`run_until_break`](Self::run_until_break)
+    /// stops after an opcode (e.g. `FX55` or `FX33`) wrote to it, like a data breakpoint
+    /// of a debugger. Writes outside of an opcode (e.g. by the `write_opcode_at` test
+    /// helper) are not watched.
+    ///
+    /// Attention while any address is watched, every memory write is checked against all
+    /// the watched addresses, so that the execution slows down with the amount of watches.
+    /// Without watches there is no overhead.
+    pub fn add_write_watch(&mut self, addr: usize) {
+        let watches = &mut self.chipset.write_watches;
+        if !watches.contains(&addr) {
+            watches.push(addr);
+        }
+    }
+
+    /// Will stop watching the memory address, returns if the address was watched.
+    pub fn remove_write_watch(&mut self, addr: usize) -> bool {
+        let watches = &mut self.chipset.write_watches;
+        let len = watches.len();
+        watches.retain(|&watch| watch != addr);
+        watches.len() != len
+    }
+
+    /// Will execute opcodes until an opcode wrote to an address watched with
+    /// [`add_write_watch`](Self::add_write_watch). At most `max` opcodes are executed. The
+    /// program counter points past the writing opcode, once this returns
+    /// [`WriteWatch`](RunStop::WriteWatch).
+    pub fn run_until_break(&mut self, max: usize) -> Result<RunStop, ProcessError> {
+        if self.is_paused() {
+            return Ok(RunStop::Paused);
+        }
+
+        for _ in 0..max {
+            self.chipset.write_watch_hit = None;
+            let operation = self.step()?;
+
+            if let Some(addr) = self.chipset.write_watch_hit.take() {
+                return Ok(RunStop::WriteWatch { addr });
+            }
+
+            if operation == Operation::Wait {
+                return Ok(RunStop::Wait);
+            }
+        }
+
+        Ok(RunStop::CycleLimit)
+    }
+
+    /// Will execute `cycles` opcodes and return the resulting display, so that the
+    /// output of a test rom can be compared against a known good frame. The execution
+    /// stops early once the rom waits for a key press, as no further progress can be
+    /// made without input. A double buffered display is presented before returning.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use chip::{chip8::ChipSet, resources::RomArchives, timer::{NoCallback, Worker}};
+    /// let mut chip: ChipSet<Worker, NoCallback> = RomArchives::new().load("IBMLOGO").unwrap();
+    /// let frame = chip.run_to_display(100).unwrap();
+    /// assert!(frame.rows().flatten().any(|&pixel| pixel));
+    /// ```
+    pub fn run_to_display(&mut self, cycles: usize) -> Result<Frame<'_>, ProcessError> {
+        for _ in 0..cycles {
+            if self.step()? == Operation::Wait {
+                break;
+            }
+        }
+        self.present();
+        Ok(self.frame())
+    }
+
+    /// Will move the program counter past the next opcode without executing it, e.g. to
+    /// skip over a known bad instruction. A pending wait for a key press is cancelled.
+    ///
+    /// Fails with [`MemoryOutOfBounds`](ProcessError::MemoryOutOfBounds), if there is no
+    /// opcode left in memory after the current one.
+    pub fn skip_instruction(&mut self) -> Result<(), ProcessError> {
+        let target = self.chipset().program_counter + memory::opcodes::SIZE;
+        self.move_program_counter(target)
+    }
+
+    /// Will move the program counter back by a single opcode. A pending wait for a key
+    /// press is cancelled.
+    ///
+    /// Fails with [`MemoryOutOfBounds`](ProcessError::MemoryOutOfBounds), if the program
+    /// counter would move before the start of the program at `0x200`.
+    pub fn rewind_instruction(&mut self) -> Result<(), ProcessError> {
+        let pc = self.chipset().program_counter;
+        let target = pc.saturating_sub(memory::opcodes::SIZE);
+        if pc < cpu::PROGRAM_COUNTER + memory::opcodes::SIZE {
+            return Err(ProcessError::MemoryOutOfBounds(target));
+        }
+        self.move_program_counter(target)
+    }
+
+    fn move_program_counter(&mut self, target: usize) -> Result<(), ProcessError> {
+        if target + memory::opcodes::SIZE > memory::SIZE {
+            return Err(ProcessError::MemoryOutOfBounds(target));
+        }
+        let chip = &mut self.chipset;
+        chip.program_counter = target;
+        chip.preprocessor = None;
+        chip.pending_draw = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+//! The events emitted by the [`chipset`](super::ChipSet) to its subscribers, so that the
+//! components of a frontend can react to the emulation without polling the chip.
+//! This implementation was split up into this file for smaller file sizes and higher
+//! cohesion.
+
+use super::ChipSet;
+use crate::timer::{TimedWorker, TimerCallback};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// The kinds of [`events`](Event), that can be subscribed to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum EventKind {
+    /// See [`Event::Draw`].
+    Draw,
+    /// See [`Event::Beep`].
+    Beep,
+    /// See [`Event::Breakpoint`].
+    Breakpoint,
+    /// See [`Event::Halt`].
+    Halt,
+}
+
+/// Something observable, that happened while the chip was running.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Event {
+    /// A `DXYN`, `00E0` or a scroll changed the display.
+    Draw,
+    /// A `FX18` started a beep.
+    Beep {
+        /// The value the sound timer was set to.
+        duration: u8,
+    },
+    /// An opcode wrote to a watched memory address, see
+    /// [`add_write_watch`](ChipSet::add_write_watch).
+    Breakpoint {
+        /// The watched address, that was written.
+        addr: usize,
+    },
+    /// The program exited the interpreter with `00FD`.
+    Halt,
+}
+
+impl Event {
+    /// Will return the kind of the event.
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::Draw => EventKind::Draw,
+            Self::Beep { .. } => EventKind::Beep,
+            Self::Breakpoint { .. } => EventKind::Breakpoint,
+            Self::Halt => EventKind::Halt,
+        }
+    }
+}
+
+/// The channels of all the subscribers of a chip.
+#[derive(Default)]
+pub(super) struct Subscribers {
+    senders: Vec<(EventKind, Sender<Event>)>,
+}
+
+impl Subscribers {
+    /// Will create a new channel receiving the events of the given kind.
+    pub(super) fn subscribe(&mut self, kind: EventKind) -> Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        self.senders.push((kind, sender));
+        receiver
+    }
+
+    /// Will return the amount of subscribers.
+    #[cfg(test)]
+    pub(super) fn len(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// Will send the event to every subscriber of its kind, dropping the subscribers
+    /// whose receiver is gone.
+    pub(super) fn emit(&mut self, event: Event) {
+        if self.senders.is_empty() {
+            return;
+        }
+        let kind = event.kind();
+        self.senders
+            .retain(|(subscribed, sender)| *subscribed != kind || sender.send(event).is_ok());
+    }
+}
+
+impl<W, S> ChipSet<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    /// Will return a receiver of all the events of the given kind, that happen from now
+    /// on. The events are sent while the chip executes an opcode, so that they can be read
+    /// from any thread without blocking the emulation. A subscription ends once its
+    /// receiver is dropped, there is no limit on the amount of subscriptions.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use chip::{chip8::{ChipSet, Event, EventKind}, resources::Rom, timer::{NoCallback, Worker}};
+    /// // LD V0, 1; DRW V0, V0, 1
+    /// let rom = Rom::from_bytes("DRAW", &[0x60, 0x01, 0xD0, 0x01]);
+    /// let mut chip: ChipSet<Worker, NoCallback> = ChipSet::new(rom);
+    /// let draws = chip.subscribe(EventKind::Draw);
+    /// chip.step().unwrap();
+    /// assert!(draws.try_recv().is_err());
+    /// chip.step().unwrap();
+    /// assert_eq!(Ok(Event::Draw), draws.try_recv());
+    /// ```
+    pub fn subscribe(&mut self, kind: EventKind) -> Receiver<Event> {
+        self.chipset.subscribers.subscribe(kind)
+    }
+}
@@ -0,0 +1,63 @@
+//! A read only view of the display of the [`chipset`](super::ChipSet).
+
+/// Borrows the pixels of a display, so that they can be read independently of how the
+/// display is stored internally.
+///
+/// The pixels are stored row by row, where `(0, 0)` is the upper left corner.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Frame<'a> {
+    /// The rows of the display.
+    pixels: &'a [Vec<bool>],
+}
+
+impl<'a> Frame<'a> {
+    /// Creates a frame from the given rows of pixels, all rows are expected to be of the
+    /// same length.
+    pub fn new(pixels: &'a [Vec<bool>]) -> Self {
+        Self { pixels }
+    }
+
+    /// The amount of pixels in a single row.
+    pub fn width(&self) -> usize {
+        self.pixels.first().map_or(0, |row| row.len())
+    }
+
+    /// The amount of rows.
+    pub fn height(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// Will return the pixel at the given coordinates or `None` if they are out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<bool> {
+        self.pixels.get(y)?.get(x).copied()
+    }
+
+    /// Will return the given row or `None` if it is out of bounds.
+    pub fn row(&self, y: usize) -> Option<&'a [bool]> {
+        self.pixels.get(y).map(|row| &row[..])
+    }
+
+    /// Will iterate over all rows from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &'a [bool]> {
+        self.pixels.iter().map(|row| &row[..])
+    }
+
+    /// Will compare this frame with the previous one and return the `(x, y)` coordinates
+    /// of every pixel, that changed, together with its new state. Unlike the
+    /// [`toggled_pixels`](super::ChipSet::toggled_pixels) this works between any two
+    /// frames, e.g. ones taken from snapshots. Pixels missing in the previous frame count
+    /// as turned off.
+    pub fn diff(&self, prev: &Frame<'_>) -> Vec<(usize, usize, bool)> {
+        self.rows()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &pixel)| (x, y, pixel)))
+            .filter(|&(x, y, pixel)| prev.get(x, y).unwrap_or(false) != pixel)
+            .collect()
+    }
+}
+
+impl<'a> AsRef<[Vec<bool>]> for Frame<'a> {
+    fn as_ref(&self) -> &[Vec<bool>] {
+        self.pixels
+    }
+}
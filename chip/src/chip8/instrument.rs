@@ -0,0 +1,41 @@
+//! The hooks called around the execution of every opcode of the
+//! [`chipset`](super::ChipSet), which are only available with the `instrument` feature.
+//! This implementation was split up into this file for smaller file sizes and higher
+//! cohesion.
+
+use super::ChipSet;
+use crate::{
+    opcode::{Opcodes, Operation},
+    timer::{TimedWorker, TimerCallback},
+};
+
+/// Is called with the opcode, that is about to be executed.
+pub type BeforeExecute = Box<dyn FnMut(&Opcodes) + Send>;
+/// Is called with the executed opcode and the operation it returned.
+pub type AfterExecute = Box<dyn FnMut(&Opcodes, &Operation) + Send>;
+
+/// The hooks registered on a chip.
+#[derive(Default)]
+pub(super) struct Hooks {
+    pub(super) before: Option<BeforeExecute>,
+    pub(super) after: Option<AfterExecute>,
+}
+
+impl<W, S> ChipSet<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    /// Will set the hook, that is called before every opcode is executed, replacing the
+    /// previous one. The hook only receives the decoded opcode, the state of the chip
+    /// can be read in between the steps.
+    pub fn set_before_execute(&mut self, hook: BeforeExecute) {
+        self.chipset.hooks.before = Some(hook);
+    }
+
+    /// Will set the hook, that is called after every successfully executed opcode,
+    /// replacing the previous one.
+    pub fn set_after_execute(&mut self, hook: AfterExecute) {
+        self.chipset.hooks.after = Some(hook);
+    }
+}
@@ -1,11 +1,37 @@
 //! The full implementation of the chip8 enumalator, from the opcodes to an option to pretty
 //! print them.
+mod audio;
+mod builder;
 mod chipset;
+mod debug;
+mod events;
+mod frame;
+#[cfg(feature = "instrument")]
+mod instrument;
 mod opcodes;
 mod print;
+mod snapshot;
+#[cfg(feature = "metrics")]
+mod stats;
+/// helpers for building chipset states in tests
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+#[cfg(feature = "trace")]
+mod trace;
 
 /// reexport chipset structs and data for simpler usage
+pub use builder::ChipSetBuilder;
 pub use chipset::*;
+pub use debug::{RunStop, StepReport};
+pub use events::{Event, EventKind};
+pub use frame::Frame;
+#[cfg(feature = "instrument")]
+pub use instrument::{AfterExecute, BeforeExecute};
+pub use snapshot::Snapshot;
+#[cfg(feature = "metrics")]
+pub use stats::Stats;
+#[cfg(feature = "trace")]
+pub use trace::MemWrite;
 
 /// split up tests into an other file for simpler implementation
 #[cfg(test)]
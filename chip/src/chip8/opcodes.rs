@@ -3,23 +3,40 @@
 //! cohesion.
 
 use crate::{
-    definitions::{cpu, display},
+    constants::FONT_CHARACTER_SIZE,
+    definitions::{cpu, display, keyboard, memory},
     opcode::*,
+    quirks::DrawMode,
     ProcessError,
 };
 
-use super::InternalChipSet;
+use super::{chipset::PendingDraw, Event, InternalChipSet};
+
+/// The amount of pixels the display is scrolled by `00FB` and `00FC`.
+const SCROLL_SIDEWAYS: usize = 4;
 
 impl ChipOpcodes for InternalChipSet {
+    #[cfg(feature = "instrument")]
+    fn before_execute(&mut self, opcode: &Opcodes) {
+        if let Some(hook) = self.hooks.before.as_mut() {
+            hook(opcode);
+        }
+    }
+
+    #[cfg(feature = "instrument")]
+    fn after_execute(&mut self, opcode: &Opcodes, operation: &Operation) {
+        if let Some(hook) = self.hooks.after.as_mut() {
+            hook(opcode, operation);
+        }
+    }
+
     fn zero(&mut self, opcode: &Zero) -> Result<(ProgramCounterStep, Operation), ProcessError> {
         match opcode {
             Zero::Clear => {
                 // 00E0
                 // clear display
-                for row in self.display.iter_mut() {
-                    row.fill(false);
-                }
-                Ok((ProgramCounterStep::Next, Operation::Draw))
+                self.fill_display(false);
+                Ok((ProgramCounterStep::Next, Operation::Clear))
             }
             Zero::Return => {
                 // 00EE
@@ -27,6 +44,44 @@ impl ChipOpcodes for InternalChipSet {
                 let pc = self.pop_stack()?;
                 Ok((ProgramCounterStep::Jump(pc), Operation::None))
             }
+            &Zero::ScrollDown(n) => {
+                // 00CN
+                // Scroll the display down by N pixels, the rows at the top are cleared
+                self.display.rotate_right(n);
+                for row in self.display[..n].iter_mut() {
+                    row.fill(false);
+                }
+                let dy = n as i32;
+                Ok((ProgramCounterStep::Next, Operation::Scroll { dx: 0, dy }))
+            }
+            Zero::ScrollRight => {
+                // 00FB
+                // Scroll the display right by 4 pixels, the columns at the left are cleared
+                for row in self.display.iter_mut() {
+                    row.rotate_right(SCROLL_SIDEWAYS);
+                    row[..SCROLL_SIDEWAYS].fill(false);
+                }
+                let dx = SCROLL_SIDEWAYS as i32;
+                Ok((ProgramCounterStep::Next, Operation::Scroll { dx, dy: 0 }))
+            }
+            Zero::ScrollLeft => {
+                // 00FC
+                // Scroll the display left by 4 pixels, the columns at the right are cleared
+                for row in self.display.iter_mut() {
+                    row.rotate_left(SCROLL_SIDEWAYS);
+                    let width = row.len();
+                    row[(width - SCROLL_SIDEWAYS)..].fill(false);
+                }
+                let dx = -(SCROLL_SIDEWAYS as i32);
+                Ok((ProgramCounterStep::Next, Operation::Scroll { dx, dy: 0 }))
+            }
+            Zero::Exit => {
+                // 00FD
+                // Exit the interpreter => stop executing
+                self.halted = true;
+                self.subscribers.emit(Event::Halt);
+                Ok((ProgramCounterStep::None, Operation::Exit))
+            }
         }
     }
 
@@ -123,12 +178,9 @@ impl ChipOpcodes for InternalChipSet {
                 // 8XY5
                 // VY is subtracted from VX. VF is set to 0 when there's a borrow, and 1 when there
                 // isn't.
-                let left = self.registers[x] as u16;
-                let right = ((!self.registers[y]).wrapping_add(1)) as u16;
-                let res = left + right;
-                let carry = (res & 0x0100) == 0x0100;
-                self.registers[x] = res as u8;
-                self.registers[cpu::register::LAST] = if carry { 1 } else { 0 };
+                let (res, borrow) = self.registers[x].overflowing_sub(self.registers[y]);
+                self.registers[x] = res;
+                self.registers[cpu::register::LAST] = !borrow as u8;
             }
             EightOpcode::Six => {
                 // 8XY6
@@ -141,12 +193,9 @@ impl ChipOpcodes for InternalChipSet {
                 // 8XY7
                 // Sets VX to VY minus VX. VF is set to 0 when there's a borrow, and 1 when there
                 // isn't.
-                let left = self.registers[y] as u16;
-                let right = ((!self.registers[x]).wrapping_add(1)) as u16;
-                let res = left + right;
-                let carry = (res & 0x0100) == 0x0100;
-                self.registers[x] = res as u8;
-                self.registers[cpu::register::LAST] = if carry { 1 } else { 0 };
+                let (res, borrow) = self.registers[y].overflowing_sub(self.registers[x]);
+                self.registers[x] = res;
+                self.registers[cpu::register::LAST] = !borrow as u8;
             }
             EightOpcode::E => {
                 // 8XYE
@@ -183,7 +232,13 @@ impl ChipOpcodes for InternalChipSet {
         // BNNN
         // Jumps to the address NNN plus V0.
         let v0 = self.registers[0] as usize;
-        Ok(ProgramCounterStep::Jump(v0 + nnn))
+        let target = v0 + nnn;
+        // the offset can move the target past the memory, which is a broken rom
+        // rather than a reason to panic
+        if target >= memory::SIZE {
+            return Err(ProcessError::MemoryOutOfBounds(target));
+        }
+        Ok(ProgramCounterStep::Jump(target))
     }
 
     fn c(&mut self, &Twelve { x, nn }: &Twelve) -> Result<ProgramCounterStep, ProcessError> {
@@ -213,26 +268,69 @@ impl ChipOpcodes for InternalChipSet {
 
         let (reg_x, reg_y, n) = (x, y, n);
 
+        // DXY0 has no rows and draws nothing in the classic Chip8. The SuperChip
+        // reinterprets it as a 16x16 sprite in the high resolution, which is not supported
+        // yet, so that it draws nothing in either mode.
+        if n == 0 {
+            self.registers[cpu::register::LAST] = 0;
+            self.last_collision.fill(false);
+            if let Some(toggled) = self.toggled_pixels.as_mut() {
+                toggled.clear();
+            }
+            return Ok((ProgramCounterStep::Next, Operation::None));
+        }
+
         let index = self.index_register;
-        let coorx = self.registers[reg_x] as usize;
-        let coory = self.registers[reg_y] as usize;
 
-        let coorx = coorx % display::HEIGHT;
-        let coory = coory % display::WIDTH;
+        // a sprite drawn row by row continues where the last step stopped
+        let PendingDraw {
+            row: first,
+            x: coorx,
+            y: coory,
+        } = match self.pending_draw.take() {
+            Some(pending) => pending,
+            None => {
+                let coorx = self.registers[reg_x] as usize % display::HEIGHT;
+                let coory = self.registers[reg_y] as usize % display::WIDTH;
+
+                // Set VF to 0
+                self.registers[cpu::register::LAST] = 0;
+                self.last_collision.fill(false);
+                #[cfg(feature = "metrics")]
+                {
+                    self.stats.sprites_drawn += 1;
+                }
+                if let Some(reads) = self.sprite_reads.as_mut() {
+                    reads[index..(index + n)].fill(true);
+                }
+
+                PendingDraw {
+                    row: 0,
+                    x: coorx,
+                    y: coory,
+                }
+            }
+        };
+
+        if let Some(toggled) = self.toggled_pixels.as_mut() {
+            toggled.clear();
+        }
 
-        // Set VF to 0
-        self.registers[cpu::register::LAST] = 0;
+        // the rows below the display are clipped
+        let rows = n.min(display::WIDTH - coory);
+        let last = if self.slow_draw {
+            (first + 1).min(rows)
+        } else {
+            rows
+        };
 
         const BYTE: usize = 8;
 
         // Get one byte of sprite data from the memory address in the I register
-        for (i, row) in self.memory[index..(index + n)].iter().enumerate() {
+        for i in first..last {
+            let row = self.memory[index + i];
             let y = coory + i;
 
-            if y >= display::WIDTH {
-                break;
-            }
-
             // - If the current pixel in the sprite row is 'on' and the pixel at coordinates X,Y
             //   on the screen is also 'on', turn 'off' the pixel and set VF to '1'.
             // - Or if the current pixel in the sprite row is 'on' and the screen pixel is 'not',
@@ -248,27 +346,56 @@ impl ChipOpcodes for InternalChipSet {
                     break;
                 }
 
-                let cpixel = (*row & mask) == mask;
+                let cpixel = (row & mask) == mask;
+                let spixel = self.display[y][x];
 
-                if !cpixel {
+                let pixel = match self.quirks.draw_mode {
+                    DrawMode::Xor => spixel ^ cpixel,
+                    DrawMode::Or => spixel | cpixel,
+                    DrawMode::Overwrite => cpixel,
+                };
+                if pixel == spixel {
                     continue;
                 }
 
-                let spixel = self.display[y][x];
-
-                self.display[y][x] = !spixel;
+                self.display[y][x] = pixel;
+                if let Some(toggled) = self.toggled_pixels.as_mut() {
+                    toggled.push((x, y));
+                }
+                #[cfg(feature = "metrics")]
+                {
+                    self.stats.pixels_toggled += 1;
+                }
 
-                if spixel {
+                // only the XOR turns off pixels as a collision
+                if spixel && self.quirks.draw_mode == DrawMode::Xor {
                     self.registers[cpu::register::LAST] = 1;
+                    self.last_collision[y * display::HEIGHT + x] = true;
                 }
             }
         }
 
+        // keep the program counter on this opcode, until the last row was drawn
+        if last < rows {
+            self.pending_draw = Some(PendingDraw {
+                row: last,
+                x: coorx,
+                y: coory,
+            });
+            return Ok((ProgramCounterStep::None, Operation::Draw));
+        }
+
+        if self.registers[cpu::register::LAST] == 1 {
+            if let Some(callback) = self.on_collision.as_mut() {
+                callback();
+            }
+        }
+
         Ok((ProgramCounterStep::Next, Operation::Draw))
     }
 
-    fn e(&self, &Fourteen { ops, x }: &Fourteen) -> Result<ProgramCounterStep, ProcessError> {
-        let is_pressed = self.get_keyboard_read().get_keys()[self.registers[x] as usize];
+    fn e(&mut self, &Fourteen { ops, x }: &Fourteen) -> Result<ProgramCounterStep, ProcessError> {
+        let is_pressed = self.poll_key(self.registers[x] as usize);
         let step = match ops {
             FourteenOpcode::Pressed => {
                 // EX9E
@@ -301,7 +428,11 @@ impl ChipOpcodes for InternalChipSet {
             FifteenOpcode::SetSoundTimer => {
                 // FX18
                 // Sets the sound timer to VX.
-                self.sound_timer.set_value(self.registers[x]);
+                let duration = self.registers[x];
+                self.sound_timer.set_value(duration);
+                if duration > 0 {
+                    self.subscribers.emit(Event::Beep { duration });
+                }
             }
             FifteenOpcode::GetDelayTimer => {
                 // FX07
@@ -312,11 +443,28 @@ impl ChipOpcodes for InternalChipSet {
                 // FX0A
                 // A key press is awaited, and then stored in VX. (Blocking Operation. All
                 // instruction halted until next key event)
+                //
+                // If multiple keys were pressed since the wait started, the lowest key index
+                // is stored, so that the result does not depend on the order in which the
+                // host delivered the presses. Without a newly held key the last changed key
+                // is used, as it might have been pressed and released in between.
+                let held = self.key_bitmask();
                 let callback_after_keypress = move |chip: &mut Self| {
-                    let last = chip.get_keyboard_read().get_last().expect(
-                        "The contract that states a last key has to be set was not fullfilled.",
-                    );
-                    chip.registers[x] = last.get_index() as u8;
+                    let pressed = {
+                        let new = chip.key_bitmask() & !held;
+                        let keyboard = chip.get_keyboard_read();
+                        (0..keyboard::SIZE)
+                            .find(|&key| new & (1 << key) != 0 && chip.is_key_enabled(key))
+                            .or_else(|| keyboard.get_last().map(|last| last.get_index()))
+                            .expect(
+                                "The contract that states a last key has to be set was not fullfilled.",
+                            )
+                    };
+                    // a disabled key is ignored, so the opcode will keep waiting
+                    if !chip.is_key_enabled(pressed) {
+                        return;
+                    }
+                    chip.registers[x] = pressed as u8;
                     // move the counter to the next instruction
                     chip.step(ProgramCounterStep::Next);
                 };
@@ -346,7 +494,7 @@ impl ChipOpcodes for InternalChipSet {
                     "There was a too large number in register <{:#X}> for hex representation.",
                     x
                 );
-                self.index_register = display::fontset::LOCATION + 5 * val;
+                self.index_register = display::fontset::LOCATION + FONT_CHARACTER_SIZE * val;
             }
             FifteenOpcode::StoreBCD => {
                 // FX33
@@ -358,16 +506,20 @@ impl ChipOpcodes for InternalChipSet {
                 let i = self.index_register;
                 let r = self.registers[x];
 
-                self.memory[i] = r / 100; // 246u8 / 100 => 2
-                self.memory[i + 1] = r / 10 % 10; // 246u8 / 10 => 24 % 10 => 4
-                self.memory[i + 2] = r % 10; // 246u8 % 10 => 6
+                let digits = [
+                    r / 100,     // 246u8 / 100 => 2
+                    r / 10 % 10, // 246u8 / 10 => 24 % 10 => 4
+                    r % 10,      // 246u8 % 10 => 6
+                ];
+                self.write_memory_bounded(i, &digits)?;
             }
             FifteenOpcode::StoreV0ToVx => {
                 // FX55
                 // Stores V0 to VX (including VX) in memory starting at address I. The offset from I
                 // is increased by 1 for each value written, but I itself is left unmodified.
                 let index = self.index_register;
-                self.memory[index..=(index + x)].copy_from_slice(&self.registers[..=x]);
+                let registers = self.registers;
+                self.write_memory_bounded(index, &registers[..=x])?;
             }
             FifteenOpcode::FillV0ToVx => {
                 // FX65
@@ -375,7 +527,20 @@ impl ChipOpcodes for InternalChipSet {
                 // offset from I is increased by 1 for each value written, but I itself is left
                 // unmodified.
                 let index = self.index_register;
-                self.registers[..=x].copy_from_slice(&self.memory[index..=(index + x)]);
+                let mut values = [0; cpu::register::SIZE];
+                self.read_memory_bounded(index, &mut values[..=x])?;
+                self.registers[..=x].copy_from_slice(&values[..=x]);
+            }
+            FifteenOpcode::StoreFlags => {
+                // FX75 (SCHIP)
+                // Stores V0 to VX (including VX) in the RPL user flags, the mode has already
+                // been checked to support it during decoding.
+                self.rpl_flags[..=x].copy_from_slice(&self.registers[..=x]);
+            }
+            FifteenOpcode::LoadFlags => {
+                // FX85 (SCHIP)
+                // Fills V0 to VX (including VX) with the values of the RPL user flags.
+                self.registers[..=x].copy_from_slice(&self.rpl_flags[..=x]);
             }
         }
         Ok((pcs, op))
@@ -329,9 +329,10 @@ mod bool_print {
 impl fmt::Display for InternalChipSet {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // prepate the rom name
-        let mut nam = String::with_capacity(INDENT_SIZE + self.name.len());
+        let name = self.rom.get_name();
+        let mut nam = String::with_capacity(INDENT_SIZE + name.len());
         indent_helper(&mut nam, INDENT_SIZE);
-        nam.push_str(&self.name);
+        nam.push_str(name);
 
         // keeping the strings mutable so that they can be indented later on
         let mem = opcode_print::printer(&self.memory, INDENT_SIZE);
@@ -381,6 +382,7 @@ mod tests {
     //     assert_eq!(&result, text_expected);
     // }
 
+    #[allow(clippy::redundant_static_lifetimes)]
     const OUTPUT_PRINT: &'static str = "\
         Chipset {\n\
             \tProgram Name :\n\
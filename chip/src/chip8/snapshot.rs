@@ -0,0 +1,366 @@
+//! The save states written for the [`chipset`](super::ChipSet).
+//! This implementation was split up into this file for smaller file sizes and higher
+//! cohesion.
+
+use super::ChipSet;
+use crate::{
+    definitions::{cpu, display, memory},
+    resources::Rom,
+    timer::{TimedWorker, TimerCallback},
+    SnapshotError,
+};
+use std::convert::TryInto;
+
+/// The name of the rom created from a memory image.
+const MEMORY_ROM_NAME: &str = "MEMORY";
+
+/// The header of a compressed snapshot, the last byte is the version of the format.
+const COMPRESSED_HEADER: [u8; 4] = *b"C8S\x01";
+
+/// A copy of the complete machine state of a chip, that can be restored later on.
+///
+/// With the `serde` feature the snapshot can be serialized.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// The name of the loaded rom.
+    pub rom_name: String,
+    /// The data of the loaded rom, so that the chip can be reset after a restore.
+    pub rom: Vec<u8>,
+    /// The complete memory.
+    pub memory: Vec<u8>,
+    /// The data registers `V0` to `VF`.
+    pub registers: [u8; cpu::register::SIZE],
+    /// The index register `I`.
+    pub index_register: usize,
+    /// The program counter.
+    pub program_counter: usize,
+    /// The return addresses on the stack, the oldest first.
+    pub stack: Vec<usize>,
+    /// The value of the delay timer.
+    pub delay_timer: u8,
+    /// The value of the sound timer.
+    pub sound_timer: u8,
+    /// The rows of the display.
+    pub display: Vec<Vec<bool>>,
+}
+
+impl<W, S> ChipSet<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    /// Creates a new chip from the given snapshot.
+    pub fn from_snapshot(snapshot: Snapshot) -> Result<Self, SnapshotError> {
+        // checked before, as the rom has to fit into the memory of the new chip
+        validate(&snapshot)?;
+        let rom = Rom::from_bytes(&snapshot.rom_name, &snapshot.rom);
+        let mut chipset =
+            Self::try_new(rom).map_err(|_| SnapshotError::RomSize(snapshot.rom.len()))?;
+        chipset.restore(snapshot)?;
+        Ok(chipset)
+    }
+
+    /// Creates a new chip from a raw memory image, as exported by other emulators.
+    ///
+    /// - `memory` - The complete memory of `4096` bytes, where the program starts at
+    ///   `0x200`. The font is expected at `0x050` as usual.
+    /// - `registers` - The `16` data registers `V0` to `VF`, all `0` if missing.
+    /// - `program_counter` - The address of the next opcode, `0x200` if missing.
+    /// - `index_register` - The value of `I`, `0` if missing.
+    /// - `stack` - The return addresses, the oldest first, empty if missing.
+    ///
+    /// The memory from `0x200` on is used as the rom, so that a reset will restart the
+    /// program of the image.
+    pub fn with_memory(
+        memory: Vec<u8>,
+        registers: Option<&[u8]>,
+        program_counter: Option<usize>,
+        index_register: Option<usize>,
+        stack: Option<&[usize]>,
+    ) -> Result<Self, SnapshotError> {
+        // checked before, as the rom has to fit into the memory
+        if memory.len() != memory::SIZE {
+            return Err(SnapshotError::MemorySize(memory.len()));
+        }
+        let registers = match registers {
+            Some(registers) => registers
+                .try_into()
+                .map_err(|_| SnapshotError::RegisterSize(registers.len()))?,
+            None => [0; cpu::register::SIZE],
+        };
+
+        Self::from_snapshot(Snapshot {
+            rom_name: MEMORY_ROM_NAME.to_string(),
+            rom: memory[cpu::PROGRAM_COUNTER..].to_vec(),
+            memory,
+            registers,
+            index_register: index_register.unwrap_or(0),
+            program_counter: program_counter.unwrap_or(cpu::PROGRAM_COUNTER),
+            stack: stack.unwrap_or_default().to_vec(),
+            delay_timer: 0,
+            sound_timer: 0,
+            display: vec![vec![false; display::HEIGHT]; display::WIDTH],
+        })
+    }
+
+    /// Will take a snapshot of the current machine state.
+    pub fn snapshot(&self) -> Snapshot {
+        let chip = self.chipset();
+        Snapshot {
+            rom_name: chip.rom.get_name().to_string(),
+            rom: chip.rom.get_data().to_vec(),
+            memory: chip.memory.clone(),
+            registers: chip.registers,
+            index_register: chip.index_register,
+            program_counter: chip.program_counter,
+            stack: chip.stack.to_vec(),
+            delay_timer: chip.get_delay_timer(),
+            sound_timer: chip.get_sound_timer(),
+            display: chip.display.clone(),
+        }
+    }
+
+    /// Will restore the machine state of the snapshot, the loaded rom is kept.
+    ///
+    /// An opcode waiting for a key press will wait again after the restore.
+    pub fn restore(&mut self, snapshot: Snapshot) -> Result<(), SnapshotError> {
+        validate(&snapshot)?;
+
+        let chip = &mut self.chipset;
+        chip.set_memory(snapshot.memory);
+        chip.opcode_memory.clear();
+        chip.registers = snapshot.registers;
+        chip.index_register = snapshot.index_register;
+        chip.program_counter = snapshot.program_counter;
+        chip.stack.clear();
+        chip.stack.extend(snapshot.stack);
+        chip.delay_timer.set_value(snapshot.delay_timer);
+        chip.sound_timer.set_value(snapshot.sound_timer);
+        chip.display = snapshot.display;
+        chip.present();
+        chip.preprocessor = None;
+        chip.pending_draw = None;
+        // the program counter still points at the exit opcode, so that it is run again
+        chip.halted = false;
+
+        Ok(())
+    }
+}
+
+impl Snapshot {
+    /// Will encode the snapshot into a compact binary format.
+    ///
+    /// The encoded state is run length compressed, so that the memory, which mostly
+    /// consists of zeros, and the display only take up a few bytes.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use chip::{chip8::{ChipSet, Snapshot}, resources::RomArchives, timer::{NoCallback, Worker}};
+    /// let chip: ChipSet<Worker, NoCallback> = RomArchives::new().load("PONG").unwrap();
+    /// let snapshot = chip.snapshot();
+    /// let compressed = snapshot.to_compressed();
+    /// assert!(compressed.len() < snapshot.memory.len());
+    /// assert_eq!(Ok(snapshot), Snapshot::from_compressed(&compressed));
+    /// ```
+    pub fn to_compressed(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        let write_slice = |data: &mut Vec<u8>, slice: &[u8]| {
+            data.extend_from_slice(&(slice.len() as u32).to_be_bytes());
+            data.extend_from_slice(slice);
+        };
+        write_slice(&mut data, self.rom_name.as_bytes());
+        write_slice(&mut data, &self.rom);
+        write_slice(&mut data, &self.memory);
+        data.extend_from_slice(&self.registers);
+        data.extend_from_slice(&(self.index_register as u32).to_be_bytes());
+        data.extend_from_slice(&(self.program_counter as u32).to_be_bytes());
+        data.push(self.stack.len() as u8);
+        for address in &self.stack {
+            data.extend_from_slice(&(*address as u32).to_be_bytes());
+        }
+        data.push(self.delay_timer);
+        data.push(self.sound_timer);
+        let pixels: Vec<_> = self.display.iter().flatten().map(|&p| p as u8).collect();
+        write_slice(&mut data, &pixels);
+
+        let mut compressed = COMPRESSED_HEADER.to_vec();
+        compressed.extend(run_length_encode(&data));
+        compressed
+    }
+
+    /// Will decode a snapshot created by [`to_compressed`](Self::to_compressed).
+    pub fn from_compressed(compressed: &[u8]) -> Result<Self, SnapshotError> {
+        let data = compressed
+            .strip_prefix(&COMPRESSED_HEADER[..])
+            .ok_or(SnapshotError::Malformed)?;
+        let data = run_length_decode(data)?;
+        let mut reader = Reader { data: &data };
+
+        let rom_name = reader.slice()?;
+        let rom_name =
+            String::from_utf8(rom_name.to_vec()).map_err(|_| SnapshotError::Malformed)?;
+        let rom = reader.slice()?.to_vec();
+        let memory = reader.slice()?.to_vec();
+        let registers = reader
+            .take(cpu::register::SIZE)?
+            .try_into()
+            .map_err(|_| SnapshotError::Malformed)?;
+        let index_register = reader.number()?;
+        let program_counter = reader.number()?;
+        let stack = (0..reader.byte()?)
+            .map(|_| reader.number())
+            .collect::<Result<_, _>>()?;
+        let delay_timer = reader.byte()?;
+        let sound_timer = reader.byte()?;
+        let pixels = reader.slice()?;
+        if pixels.len() != display::RESOLUTION || !reader.data.is_empty() {
+            return Err(SnapshotError::Malformed);
+        }
+        let display = pixels
+            .chunks(display::HEIGHT)
+            .map(|row| row.iter().map(|&p| p != 0).collect())
+            .collect();
+
+        let snapshot = Self {
+            rom_name,
+            rom,
+            memory,
+            registers,
+            index_register,
+            program_counter,
+            stack,
+            delay_timer,
+            sound_timer,
+            display,
+        };
+        validate(&snapshot)?;
+        Ok(snapshot)
+    }
+}
+
+/// Reads the fields of an uncompressed snapshot.
+struct Reader<'a> {
+    /// The data, that has not been read yet.
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.data.len() < len {
+            return Err(SnapshotError::Malformed);
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn number(&mut self) -> Result<usize, SnapshotError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    /// Will read a slice prefixed by its length.
+    fn slice(&mut self) -> Result<&'a [u8], SnapshotError> {
+        let len = self.number()?;
+        self.take(len)
+    }
+}
+
+/// The longest run of bytes, that is stored by a single control byte.
+const MAX_RUN: usize = 128;
+
+/// Will compress the data using the PackBits scheme. Every control byte `n` is either
+/// followed by `n + 1` literal bytes (`n < 128`) or by a single byte repeated `257 - n`
+/// times (`n > 128`), so that data without repetitions barely grows.
+fn run_length_encode(data: &[u8]) -> Vec<u8> {
+    let run_length = |rest: &[u8]| {
+        rest.iter()
+            .take(MAX_RUN)
+            .take_while(|&&byte| byte == rest[0])
+            .count()
+    };
+
+    let mut encoded = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let run = run_length(rest);
+        if run > 1 {
+            encoded.push((257 - run) as u8);
+            encoded.push(rest[0]);
+            rest = &rest[run..];
+        } else {
+            // collect the literals up to the next repetition
+            let mut len = 1;
+            while len < rest.len() && len < MAX_RUN && run_length(&rest[len..]) < 2 {
+                len += 1;
+            }
+            encoded.push((len - 1) as u8);
+            encoded.extend_from_slice(&rest[..len]);
+            rest = &rest[len..];
+        }
+    }
+    encoded
+}
+
+/// Will expand the data compressed by [`run_length_encode`](run_length_encode).
+fn run_length_decode(data: &[u8]) -> Result<Vec<u8>, SnapshotError> {
+    let mut decoded = Vec::new();
+    let mut rest = data;
+    while let Some((&control, tail)) = rest.split_first() {
+        let control = control as usize;
+        rest = match control {
+            0..=127 => {
+                let literals = tail.get(..=control).ok_or(SnapshotError::Malformed)?;
+                decoded.extend_from_slice(literals);
+                &tail[control + 1..]
+            }
+            128 => return Err(SnapshotError::Malformed),
+            _ => {
+                let &value = tail.first().ok_or(SnapshotError::Malformed)?;
+                decoded.extend(std::iter::repeat_n(value, 257 - control));
+                &tail[1..]
+            }
+        };
+    }
+    Ok(decoded)
+}
+
+/// Will check that the snapshot fits into the chip.
+fn validate(snapshot: &Snapshot) -> Result<(), SnapshotError> {
+    if snapshot.memory.len() != memory::SIZE {
+        return Err(SnapshotError::MemorySize(snapshot.memory.len()));
+    }
+    if snapshot.rom.len() > memory::SIZE - cpu::PROGRAM_COUNTER {
+        return Err(SnapshotError::RomSize(snapshot.rom.len()));
+    }
+    if snapshot.stack.len() > cpu::stack::SIZE {
+        return Err(SnapshotError::StackSize(snapshot.stack.len()));
+    }
+    if let Some(&address) = snapshot
+        .stack
+        .iter()
+        .find(|&&address| address >= memory::SIZE)
+    {
+        return Err(SnapshotError::StackAddress(address));
+    }
+    if snapshot.program_counter >= memory::SIZE {
+        return Err(SnapshotError::ProgramCounter(snapshot.program_counter));
+    }
+    if snapshot.index_register >= memory::SIZE {
+        return Err(SnapshotError::IndexRegister(snapshot.index_register));
+    }
+    if snapshot.display.len() != display::WIDTH
+        || snapshot
+            .display
+            .iter()
+            .any(|row| row.len() != display::HEIGHT)
+    {
+        return Err(SnapshotError::DisplaySize);
+    }
+    Ok(())
+}
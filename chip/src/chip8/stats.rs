@@ -0,0 +1,30 @@
+//! The counters of the executed opcodes of the [`chipset`](super::ChipSet), which are
+//! only available with the `metrics` feature.
+
+use crate::opcode::Opcodes;
+
+/// Lightweight counters of what the program did since the chip was created or reset,
+/// so that the rendering and control flow intensity of a rom can be profiled.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Stats {
+    /// The amount of executed `DXYN` opcodes.
+    pub sprites_drawn: u64,
+    /// The amount of pixels toggled by all the `DXYN` opcodes, both on and off.
+    pub pixels_toggled: u64,
+    /// The amount of executed `2NNN` opcodes.
+    pub subroutine_calls: u64,
+    /// The amount of executed `1NNN` and `BNNN` opcodes.
+    pub jumps: u64,
+}
+
+impl Stats {
+    /// Will count the control flow of the opcode, that is about to be executed. The draws
+    /// are counted by the `DXYN` handler itself, as only it knows the toggled pixels.
+    pub(super) fn record(&mut self, opcode: &Opcodes) {
+        match opcode {
+            Opcodes::One(_) | Opcodes::B(_) => self.jumps += 1,
+            Opcodes::Two(_) => self.subroutine_calls += 1,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! The test helpers for the [`chipset`](super::ChipSet), these are used by the internal tests
+//! and exposed with the `test-utils` feature, so that downstream crates can build chip states
+//! without reimplementing the scaffolding.
+
+use std::fmt;
+
+use super::ChipSet;
+use crate::{
+    asm,
+    definitions::{cpu, memory},
+    opcode::{Opcode, Operation},
+    resources::{Rom, RomArchives},
+    timer::{TimedWorker, TimerCallback},
+    ProcessError,
+};
+
+/// The name of the rom used as a base for the tests.
+pub const BASE_ROM_NAME: &str = "15PUZZLE";
+
+/// Will extract the given rom from the included rom archive.
+///
+/// # Panics
+/// If there is no rom with the given name.
+pub fn get_rom(name: &str) -> Rom {
+    RomArchives::new()
+        .get_file_data(name)
+        .expect("A panic happend during extraction of the Rom archive.")
+}
+
+/// Will return the rom used as a base for the tests.
+pub fn get_base() -> Rom {
+    static BASE_ROM: once_cell::sync::Lazy<Rom> =
+        once_cell::sync::Lazy::new(|| get_rom(BASE_ROM_NAME));
+    BASE_ROM.clone()
+}
+
+/// The maximum amount of opcodes executed by [`execute_str`](ChipSet::execute_str).
+pub const EXECUTE_STR_MAX_CYCLES: usize = 10_000;
+
+/// Will write the slice to the memory location specified
+pub fn write_slice_to_memory(memory: &mut [u8], from: usize, data: &[u8]) {
+    memory[from..(from + data.len())].copy_from_slice(data);
+}
+
+impl<W, S> ChipSet<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    /// Creates a new chip set from a given rom, with the registers set to the given values.
+    pub fn with_registers(rom: Rom, registers: [u8; cpu::register::SIZE]) -> Self {
+        let mut chipset = Self::new(rom);
+        chipset.chipset_mut().registers = registers;
+        chipset
+    }
+
+    /// Will return the data registers `V0` to `VF`, so that they can be set up before
+    /// running an opcode.
+    pub fn registers_mut(&mut self) -> &mut [u8] {
+        &mut self.chipset_mut().registers
+    }
+
+    /// Will write the opcode to the memory location specified.
+    pub fn with_opcode_at(mut self, address: usize, opcode: Opcode) -> Self {
+        self.write_opcode_at(address, opcode);
+        self
+    }
+
+    /// Will write the opcode to the memory location specified, an already decoded opcode
+    /// at that location is discarded.
+    pub fn write_opcode_at(&mut self, address: usize, opcode: Opcode) {
+        let chip = self.chipset_mut();
+        chip.write_memory(address, &opcode.to_be_bytes());
+        chip.opcode_memory.remove(&address);
+    }
+
+    /// Will assemble the snippet with the [`assembler`](crate::asm), load it at the
+    /// program counter and execute it, until the program counter leaves the snippet,
+    /// e.g. by running past its last opcode. The labels resolve to the addresses at the
+    /// program counter. The execution stops early once the chip waits for a key press or
+    /// exits the interpreter.
+    ///
+    /// Fails with [`BudgetExceeded`](ProcessError::BudgetExceeded), if the snippet did
+    /// not finish within [`EXECUTE_STR_MAX_CYCLES`] opcodes, e.g. because of an endless
+    /// loop, and with [`MemoryOutOfBounds`](ProcessError::MemoryOutOfBounds), if the
+    /// snippet does not fit into the memory.
+    ///
+    /// # Panics
+    /// If the snippet can not be assembled.
+    pub fn execute_str(&mut self, asm: &str) -> Result<(), ProcessError> {
+        let start = self.chipset().program_counter;
+        let data = asm::assemble_at(asm, start)
+            .unwrap_or_else(|err| panic!("Unable to assemble the snippet: {}", err));
+        let end = start + data.len();
+        if end > memory::SIZE {
+            return Err(ProcessError::MemoryOutOfBounds(end));
+        }
+
+        let chip = self.chipset_mut();
+        chip.write_memory(start, &data);
+        chip.opcode_memory
+            .retain(|&address, _| address + memory::opcodes::SIZE <= start || address >= end);
+
+        for _ in 0..EXECUTE_STR_MAX_CYCLES {
+            let pc = self.chipset().program_counter;
+            if !(start..end).contains(&pc) {
+                return Ok(());
+            }
+            if let Operation::Wait | Operation::Exit = self.step()? {
+                return Ok(());
+            }
+        }
+
+        Err(ProcessError::BudgetExceeded)
+    }
+}
+
+/// Compares the machine state of two chips, so that `assert_eq!` can check that two
+/// chips stayed in sync after running the same inputs. The memory, the registers `V0` to
+/// `VF`, the index register, the program counter, the stack, the values of the delay and
+/// sound timer and the display are compared.
+///
+/// Everything else is excluded, most notably the loaded rom, the keyboard, the random
+/// number generator, the awaited key press, the configuration and the registered
+/// callbacks. As the timers count down on their own, they should be paused or stopped
+/// while comparing.
+impl<W, S> PartialEq for ChipSet<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    fn eq(&self, other: &Self) -> bool {
+        let (chip, other_chip) = (self.chipset(), other.chipset());
+        chip.memory == other_chip.memory
+            && chip.registers == other_chip.registers
+            && chip.index_register == other_chip.index_register
+            && chip.program_counter == other_chip.program_counter
+            && chip.stack == other_chip.stack
+            && chip.get_delay_timer() == other_chip.get_delay_timer()
+            && chip.get_sound_timer() == other_chip.get_sound_timer()
+            && chip.display == other_chip.display
+    }
+}
+
+/// Prints the whole chip like the [`Display`](fmt::Display) implementation, so that a
+/// failed comparison shows both states.
+impl<W, S> fmt::Debug for ChipSet<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
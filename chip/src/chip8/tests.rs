@@ -1,31 +1,28 @@
+// The opcode tests spell out every part of the opcodes they build (e.g. `1 * 4` for the
+// second nibble) and borrow the chip explicitly, which the following lints would flag.
+#![allow(
+    clippy::identity_op,
+    clippy::needless_borrow,
+    clippy::unnecessary_cast,
+    unused_mut
+)]
+
 use std::convert::TryInto;
 
 use crate::timer::{NoCallback, Worker};
-use crate::StackError;
+use crate::{ChipError, OpcodeError, ProcessError, SnapshotError, StackError};
 
 use crate::{
-    chip8::ChipSet,
-    definitions::{cpu, memory},
-    opcode::{ChipOpcodes, Opcode, Operation, ProgramCounter, ProgramCounterStep},
-    resources::{Rom, RomArchives},
+    chip8::{ChipSet, ChipSetBuilder, Event, EventKind, Frame, RunStop, Snapshot, StepReport},
+    definitions::{cpu, display, keyboard, memory, sound},
+    opcode::{self, ChipOpcodes, Opcode, Opcodes, Operation, ProgramCounter, ProgramCounterStep},
+    quirks::{DrawMode, Quirks, SysCall},
+    resources::Rom,
 };
 
+use super::testing::{get_base, write_slice_to_memory};
 use super::InternalChipSet;
 
-const ROM_NAME: &'static str = "15PUZZLE";
-
-static BASE_ROM: once_cell::sync::Lazy<Rom> = once_cell::sync::Lazy::new(|| get_rom(ROM_NAME));
-
-fn get_rom(s: &str) -> Rom {
-    RomArchives::new()
-        .get_file_data(s)
-        .expect("A panic happend during extraction of the Rom archive.")
-}
-
-pub(super) fn get_base() -> Rom {
-    BASE_ROM.clone()
-}
-
 /// will setup the default configured chip
 pub(super) fn get_default_chip() -> ChipSet<Worker, NoCallback> {
     let rom = get_base();
@@ -51,12 +48,6 @@ pub(super) fn write_opcode_to_memory(chip: &mut InternalChipSet, from: usize, op
     write_slice_to_memory(&mut chip.memory, from, &opcode.to_be_bytes());
 }
 
-#[inline]
-/// Will write the slice to the memory location specified
-pub(super) fn write_slice_to_memory(memory: &mut [u8], from: usize, data: &[u8]) {
-    memory[from..(from + data.len())].copy_from_slice(&data);
-}
-
 #[test]
 /// test reading of the first opcode
 fn test_set_opcode() {
@@ -70,6 +61,1593 @@ fn test_set_opcode() {
     assert_eq!(chip.get_opcode(), opcode.try_into());
 }
 
+#[test]
+/// test reading an opcode past the end of the memory
+fn test_set_opcode_out_of_memory() {
+    let mut chipset = get_default_chip();
+    let chip = chipset.chipset_mut();
+
+    let len = chip.memory.len();
+    chip.program_counter = len - 1;
+
+    assert_eq!(
+        Err(ProcessError::OpcodeFetch(OpcodeError::MemoryInvalid {
+            pointer: len - 1,
+            len
+        })),
+        chip.next()
+    );
+}
+
+#[test]
+/// test the helpers exposed for downstream tests
+fn test_testing_helpers() {
+    let registers = [0x42; cpu::register::SIZE];
+    let opcode = 0x00E0;
+    let pc = cpu::PROGRAM_COUNTER;
+    let mut chipset: ChipSet<Worker, NoCallback> =
+        ChipSet::with_registers(get_base(), registers).with_opcode_at(pc, opcode);
+    let chip = chipset.chipset_mut();
+
+    assert_eq!(registers, chip.registers);
+    assert_eq!(chip.get_opcode(), opcode.try_into());
+}
+
+#[test]
+/// test that the reset restores the state after loading the rom
+fn test_reset() {
+    let mut chipset = get_default_chip();
+    let memory = chipset.chipset().memory.clone();
+    let chip = chipset.chipset_mut();
+
+    write_opcode_to_memory(chip, cpu::PROGRAM_COUNTER, 0x00E0);
+    chip.program_counter += memory::opcodes::SIZE;
+    chip.index_register = 0x123;
+    assert_eq!(Ok(()), chip.push_stack(cpu::PROGRAM_COUNTER));
+
+    chipset.reset(true);
+    let chip = chipset.chipset();
+
+    assert_eq!(memory, chip.memory);
+    assert_eq!([0; cpu::register::SIZE], chip.registers);
+    assert_eq!(0, chip.index_register);
+    assert_eq!(cpu::PROGRAM_COUNTER, chip.program_counter);
+    assert!(chip.stack.is_empty());
+    assert!(chip.display.iter().flatten().all(|&pixel| pixel));
+}
+
+#[test]
+/// test running until the current subroutine returns
+fn test_run_until_return() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+    let sub = 0x300;
+
+    chipset.write_opcode_at(pc, 0x2000 ^ sub as Opcode);
+    chipset.write_opcode_at(sub, 0x6001);
+    chipset.write_opcode_at(sub + memory::opcodes::SIZE, 0x00EE);
+
+    // not inside of a subroutine so the limit is reached
+    assert_eq!(Ok(RunStop::CycleLimit), chipset.run_until_return(1));
+    assert_eq!(1, chipset.stack_depth());
+
+    assert_eq!(Ok(RunStop::Returned), chipset.run_until_return(10));
+    assert_eq!(0, chipset.stack_depth());
+    assert_eq!(
+        pc + memory::opcodes::SIZE,
+        chipset.chipset().program_counter
+    );
+}
+
+#[test]
+/// test stepping over a subroutine call
+fn test_step_over() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+    let sub = 0x300;
+
+    chipset.write_opcode_at(pc, 0x2000 ^ sub as Opcode);
+    chipset.write_opcode_at(pc + memory::opcodes::SIZE, 0x6002);
+    chipset.write_opcode_at(sub, 0x6001);
+    chipset.write_opcode_at(sub + memory::opcodes::SIZE, 0x00EE);
+
+    // the subroutine does not finish within the limit
+    assert_eq!(Ok(RunStop::CycleLimit), chipset.step_over(2));
+    assert_eq!(1, chipset.stack_depth());
+
+    chipset.chipset_mut().program_counter = pc;
+    chipset.chipset_mut().stack.clear();
+    assert_eq!(Ok(RunStop::Returned), chipset.step_over(10));
+    assert_eq!(0, chipset.stack_depth());
+    assert_eq!(1, chipset.registers_array()[0]);
+    assert_eq!(
+        pc + memory::opcodes::SIZE,
+        chipset.chipset().program_counter
+    );
+
+    // any other opcode is a single step
+    assert_eq!(Ok(RunStop::Stepped), chipset.step_over(10));
+    assert_eq!(2, chipset.registers_array()[0]);
+}
+
+#[test]
+/// test reporting the jumps to lower addresses
+fn test_on_backward_jump() {
+    let jumps = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let cjumps = jumps.clone();
+    let mut chipset = get_default_chip();
+    chipset.on_backward_jump(move |from, to| cjumps.lock().push((from, to)));
+
+    // CALL 0x300; JP 0x206; (skipped); JP 0x200 and RET at 0x300
+    let pc = cpu::PROGRAM_COUNTER;
+    chipset.write_opcode_at(pc, 0x2300);
+    chipset.write_opcode_at(pc + 2, 0x1206);
+    chipset.write_opcode_at(pc + 6, 0x1200);
+    chipset.write_opcode_at(0x300, 0x00EE);
+
+    for _ in 0..4 {
+        assert!(chipset.step().is_ok());
+    }
+    assert_eq!(pc, chipset.chipset().program_counter);
+    assert_eq!(vec![(0x300, 0x202), (0x206, 0x200)], *jumps.lock());
+}
+
+#[test]
+/// test creating a chip without a program
+fn test_new_empty() {
+    let chipset: ChipSet<Worker, NoCallback> = ChipSet::new_empty();
+    let chip = chipset.chipset();
+    assert_eq!(cpu::PROGRAM_COUNTER, chip.program_counter);
+    assert_eq!(&display::fontset::FONTSET[..], chipset.fontset());
+    assert!(chip.memory[cpu::PROGRAM_COUNTER..]
+        .iter()
+        .all(|&byte| byte == 0));
+    assert_eq!(0, chipset.remaining_program_bytes());
+
+    let rom = Rom::from_bytes("test", &[0x60, 0x2A]);
+    let mut chipset = chipset.fork_with_rom(rom).unwrap();
+    assert_eq!(Ok(Operation::None), chipset.step());
+    assert_eq!(0x2A, chipset.registers_array()[0]);
+}
+
+#[test]
+/// test taking and restoring a snapshot
+fn test_snapshot() {
+    let mut chipset = get_default_chip();
+    chipset.chipset_mut().display[1][2] = true;
+    chipset.chipset_mut().stack.push(0x300);
+    let snapshot = chipset.snapshot();
+
+    for _ in 0..10 {
+        let _ = chipset.step();
+    }
+    chipset.reset(true);
+    assert_ne!(snapshot, chipset.snapshot());
+
+    assert_eq!(Ok(()), chipset.restore(snapshot.clone()));
+    // the timers keep on counting down
+    let mut restored = chipset.snapshot();
+    restored.delay_timer = snapshot.delay_timer;
+    restored.sound_timer = snapshot.sound_timer;
+    assert_eq!(snapshot, restored);
+
+    let chipset: ChipSet<Worker, NoCallback> =
+        ChipSet::from_snapshot(snapshot.clone()).expect("a valid snapshot");
+    assert_eq!(snapshot.registers, chipset.chipset().registers);
+    assert_eq!(snapshot.display, chipset.get_display());
+
+    let mut invalid = snapshot.clone();
+    invalid.memory.pop();
+    assert_eq!(
+        Err(SnapshotError::MemorySize(memory::SIZE - 1)),
+        get_default_chip().restore(invalid)
+    );
+
+    let mut invalid = snapshot.clone();
+    invalid.rom = vec![0; memory::SIZE];
+    assert_eq!(
+        Err(SnapshotError::RomSize(memory::SIZE)),
+        ChipSet::<Worker, NoCallback>::from_snapshot(invalid).map(|_| ())
+    );
+
+    let mut invalid = snapshot.clone();
+    invalid.index_register = memory::SIZE;
+    assert_eq!(
+        Err(SnapshotError::IndexRegister(memory::SIZE)),
+        ChipSet::<Worker, NoCallback>::from_snapshot(invalid).map(|_| ())
+    );
+
+    let mut invalid = snapshot;
+    invalid.stack = vec![0x200, memory::SIZE + 2];
+    assert_eq!(
+        Err(SnapshotError::StackAddress(memory::SIZE + 2)),
+        get_default_chip().restore(invalid)
+    );
+}
+
+#[test]
+/// test the compression of the snapshots
+fn test_snapshot_compressed() {
+    let mut chipset = get_default_chip();
+    chipset.chipset_mut().display[1][2] = true;
+    chipset.chipset_mut().stack.push(0x300);
+    let snapshot = chipset.snapshot();
+
+    let compressed = snapshot.to_compressed();
+    // the rom is stored twice, once on its own and once in the memory
+    assert!(compressed.len() < 2 * snapshot.rom.len() + 256);
+    assert_eq!(Ok(snapshot.clone()), Snapshot::from_compressed(&compressed));
+
+    assert_eq!(
+        Err(SnapshotError::Malformed),
+        Snapshot::from_compressed(&compressed[..compressed.len() - 2])
+    );
+    assert_eq!(
+        Err(SnapshotError::Malformed),
+        Snapshot::from_compressed(&compressed[1..])
+    );
+
+    let mut invalid = snapshot;
+    invalid.stack = vec![0x200; cpu::stack::SIZE + 1];
+    assert_eq!(
+        Err(SnapshotError::StackSize(cpu::stack::SIZE + 1)),
+        Snapshot::from_compressed(&invalid.to_compressed())
+    );
+}
+
+#[test]
+/// test pausing the chip
+fn test_pause() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+    chipset.write_opcode_at(pc, 0x1000 ^ pc as Opcode);
+    assert!(!chipset.is_paused());
+
+    chipset.pause();
+    assert!(chipset.is_paused());
+    chipset.chipset_mut().delay_timer.set_value(60);
+
+    let deadline = web_time::Instant::now() + std::time::Duration::from_secs(60);
+    assert_eq!(Ok(RunStop::Paused), chipset.run_until(deadline, 10));
+
+    // the timers do not count down while paused
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(60, chipset.chipset().get_delay_timer());
+
+    assert!(!chipset.toggle_pause());
+    assert_eq!(Ok(RunStop::CycleLimit), chipset.run_until(deadline, 10));
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(chipset.chipset().get_delay_timer() < 60);
+
+    assert!(chipset.toggle_pause());
+    chipset.resume();
+    assert!(!chipset.is_paused());
+}
+
+#[test]
+/// test the sound state and its change callback
+fn test_sound_active() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+    let changes = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let cchanges = changes.clone();
+    chipset.on_sound_change(Box::new(move |active| cchanges.lock().push(active)));
+    assert!(!chipset.sound_active());
+
+    // LD V0, 2; LD ST, V0
+    chipset.write_opcode_at(pc, 0x6002);
+    chipset.write_opcode_at(pc + 2, 0xF018);
+    chipset.step().unwrap();
+    chipset.step().unwrap();
+    assert!(chipset.sound_active());
+
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    assert!(!chipset.sound_active());
+    assert_eq!(vec![true, false], *changes.lock());
+}
+
+#[test]
+/// test that a stopped timer worker is reported
+fn test_timer_failure() {
+    struct PanicCallback;
+    impl crate::timer::TimerCallback for PanicCallback {
+        fn new() -> Self {
+            Self
+        }
+        fn handle(&mut self) {
+            panic!("The sound callback failed.");
+        }
+    }
+
+    let mut chipset: ChipSet<Worker, PanicCallback> = ChipSet::new(get_base());
+    assert!(chipset.step().is_ok());
+
+    chipset.chipset_mut().sound_timer.set_value(1);
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(Err(ProcessError::TimerFailure), chipset.step());
+}
+
+#[test]
+/// test the double buffered display
+fn test_double_buffering() {
+    let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+        .with_double_buffering(true)
+        .build();
+
+    chipset.fill_display(true);
+    assert!(chipset.get_display().iter().flatten().all(|pixel| !pixel));
+
+    chipset.present();
+    assert!(chipset.get_display().iter().flatten().all(|&pixel| pixel));
+
+    // reset changes both buffers
+    chipset.reset(false);
+    assert!(chipset.get_display().iter().flatten().all(|pixel| !pixel));
+
+    // without double buffering the changes are visible right away
+    let mut chipset = get_default_chip();
+    chipset.fill_display(true);
+    assert!(chipset.get_display().iter().flatten().all(|&pixel| pixel));
+}
+
+#[test]
+/// test setting the keys from a function
+fn test_set_keys_from() {
+    let mut chipset = get_default_chip();
+    chipset.set_keys_from(|key| key % 2 == 0);
+
+    let chip = chipset.chipset();
+    let keys: Vec<_> = (0..keyboard::SIZE).map(|key| key % 2 == 0).collect();
+    assert_eq!(&keys[..], chip.get_keyboard_read().get_keys());
+
+    let last = chip.get_keyboard_read().get_last().expect("a changed key");
+    assert_eq!(keyboard::SIZE - 2, last.get_index());
+    assert!(last.get_current());
+
+    // the function may read the keyboard of the chip, e.g. to toggle the keys
+    let keyboard = chipset.chipset().keyboard.clone();
+    chipset.set_keys_from(|key| !keyboard.read().is_pressed(key));
+    assert_eq!(0b1010_1010_1010_1010, keyboard.read().bitmask());
+}
+
+#[test]
+/// test setting the keyboard from a bitmask
+fn test_set_keyboard_bitmask() {
+    let mut chipset = get_default_chip();
+    chipset.set_keyboard_bitmask(0b1000_0000_0010_0001);
+
+    let keyboard = chipset.chipset().get_keyboard_read();
+    assert_eq!(0b1000_0000_0010_0001, keyboard.bitmask());
+    assert!(keyboard.is_pressed(0x0));
+    assert!(keyboard.is_pressed(0x5));
+    assert!(keyboard.is_pressed(0xF));
+    assert!(!keyboard.is_pressed(0x1));
+    assert!(!keyboard.is_pressed(keyboard::SIZE));
+    let keys: Vec<_> = (0..keyboard::SIZE)
+        .map(|key| matches!(key, 0x0 | 0x5 | 0xF))
+        .collect();
+    assert_eq!(&keys[..], keyboard.get_keys());
+    assert!(keyboard.get_last().is_none());
+    drop(keyboard);
+
+    chipset.set_keyboard(&[false; keyboard::SIZE]);
+    assert_eq!(0, chipset.chipset().get_keyboard_read().bitmask());
+    assert!(!chipset.any_key_pressed());
+}
+
+#[test]
+/// test the detection of executed sprite data
+fn test_data_execution_check() {
+    let pc = cpu::PROGRAM_COUNTER;
+    let data = 0x300;
+    // draw the sprite and jump into it, the sprite is a valid opcode
+    let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+        .with_data_execution_check(true)
+        .build()
+        .with_opcode_at(pc, 0xA000 ^ data as Opcode)
+        .with_opcode_at(pc + 2, 0xD012)
+        .with_opcode_at(pc + 4, 0x1000 ^ data as Opcode)
+        .with_opcode_at(data, 0x6001);
+    for _ in 0..4 {
+        assert!(chipset.step().is_ok());
+    }
+    assert_eq!(&[data], chipset.data_executions());
+
+    // the check is disabled by default
+    let mut chipset = get_default_chip()
+        .with_opcode_at(pc, 0xA000 ^ data as Opcode)
+        .with_opcode_at(pc + 2, 0xD012)
+        .with_opcode_at(pc + 4, 0x1000 ^ data as Opcode)
+        .with_opcode_at(data, 0x6001);
+    for _ in 0..4 {
+        assert!(chipset.step().is_ok());
+    }
+    assert!(chipset.data_executions().is_empty());
+}
+
+#[test]
+/// test creating a chip from a raw memory image
+fn test_with_memory() {
+    let mut memory = vec![0; memory::SIZE];
+    write_slice_to_memory(&mut memory, 0x300, &[0x60, 0x2A]);
+    let registers = [1; cpu::register::SIZE];
+
+    let mut chipset: ChipSet<Worker, NoCallback> = ChipSet::with_memory(
+        memory.clone(),
+        Some(&registers),
+        Some(0x300),
+        Some(0x400),
+        Some(&[0x202]),
+    )
+    .expect("a valid memory image");
+    let chip = chipset.chipset();
+    assert_eq!(memory, chip.memory);
+    assert_eq!(registers, chip.registers);
+    assert_eq!(0x400, chip.index_register);
+    assert_eq!(1, chipset.stack_depth());
+
+    assert!(chipset.step().is_ok());
+    assert_eq!(0x2A, chipset.chipset().registers[0]);
+
+    let chipset: ChipSet<Worker, NoCallback> =
+        ChipSet::with_memory(memory.clone(), None, None, None, None).expect("a valid image");
+    assert_eq!(cpu::PROGRAM_COUNTER, chipset.chipset().program_counter);
+
+    let invalid: Result<ChipSet<Worker, NoCallback>, _> =
+        ChipSet::with_memory(memory.clone(), Some(&[0; 4]), None, None, None);
+    assert!(matches!(invalid, Err(SnapshotError::RegisterSize(4))));
+    memory.push(0);
+    let invalid: Result<ChipSet<Worker, NoCallback>, _> =
+        ChipSet::with_memory(memory, None, None, None, None);
+    assert!(matches!(invalid, Err(SnapshotError::MemorySize(_))));
+}
+
+#[test]
+/// test checking for any pressed key
+fn test_any_key_pressed() {
+    let mut chipset = get_default_chip();
+    assert!(!chipset.any_key_pressed());
+
+    chipset.set_key(0x5, true);
+    assert!(chipset.any_key_pressed());
+    assert!(chipset.chipset().get_keyboard_read().any_pressed());
+
+    chipset.set_key_mask(!(1 << 0x5));
+    assert!(!chipset.any_key_pressed());
+}
+
+#[test]
+/// test that the instruction budget stops the execution
+fn test_instruction_budget() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+    // an endless loop
+    chipset.write_opcode_at(pc, 0x1200);
+
+    assert_eq!(None, chipset.instruction_budget());
+    chipset.set_instruction_budget(Some(3));
+    for _ in 0..3 {
+        assert_eq!(Ok(Operation::None), chipset.step());
+    }
+    assert_eq!(Some(0), chipset.instruction_budget());
+    assert_eq!(Err(ProcessError::BudgetExceeded), chipset.step());
+    assert_eq!(pc, chipset.chipset().program_counter);
+
+    // the budget survives a reset
+    chipset.reset(false);
+    chipset.write_opcode_at(pc, 0x1200);
+    assert_eq!(Err(ProcessError::BudgetExceeded), chipset.step());
+
+    chipset.set_instruction_budget(None);
+    assert_eq!(Ok(Operation::None), chipset.step());
+}
+
+#[test]
+/// test the callback for unsupported opcodes
+fn test_on_unsupported() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+    let unsupported = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let cunsupported = unsupported.clone();
+    chipset.on_unsupported(move |opcode| cunsupported.lock().push(opcode));
+
+    chipset.write_opcode_at(pc, 0x0001);
+    assert_eq!(
+        Err(ProcessError::Opcode(OpcodeError::InvalidOpcode(0x0001))),
+        chipset.step()
+    );
+    assert!(chipset.debug_step().is_err());
+
+    // supported opcodes are not reported
+    chipset.write_opcode_at(pc, 0x00E0);
+    assert_eq!(Ok(Operation::Clear), chipset.step());
+    assert_eq!(vec![0x0001, 0x0001], *unsupported.lock());
+}
+
+#[test]
+/// test the configurable handling of the `0NNN` machine code calls
+fn test_sys_call() {
+    let pc = cpu::PROGRAM_COUNTER;
+    let quirks = Quirks {
+        sys_call: SysCall::Error,
+        ..Default::default()
+    };
+    let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+        .with_quirks(quirks)
+        .build()
+        .with_opcode_at(pc, 0x0123)
+        .with_opcode_at(pc + memory::opcodes::SIZE, 0x0123);
+    assert_eq!(
+        Err(ProcessError::Opcode(OpcodeError::InvalidOpcode(0x0123))),
+        chipset.step()
+    );
+    assert_eq!(pc, chipset.chipset().program_counter);
+
+    chipset.set_quirks(Quirks {
+        sys_call: SysCall::Ignore,
+        ..quirks
+    });
+    assert_eq!(Ok(Operation::None), chipset.step());
+    assert_eq!(
+        pc + memory::opcodes::SIZE,
+        chipset.chipset().program_counter
+    );
+
+    chipset.set_quirks(Quirks {
+        sys_call: SysCall::Callback,
+        ..quirks
+    });
+    let calls = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let ccalls = calls.clone();
+    chipset.on_sys_call(move |address| ccalls.lock().push(address));
+    assert_eq!(Ok(Operation::None), chipset.step());
+    assert_eq!(
+        pc + 2 * memory::opcodes::SIZE,
+        chipset.chipset().program_counter
+    );
+    assert_eq!(vec![0x123], *calls.lock());
+
+    // the other opcodes starting with zero are unaffected
+    chipset.write_opcode_at(pc + 2 * memory::opcodes::SIZE, 0x00E0);
+    assert_eq!(Ok(Operation::Clear), chipset.step());
+    assert_eq!(1, calls.lock().len());
+}
+
+#[test]
+/// test creating a chip with the same configuration, but a different rom
+fn test_fork_with_rom() {
+    let quirks = Quirks {
+        sys_call: SysCall::Ignore,
+        ..Default::default()
+    };
+    let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+        .with_pc_history(4)
+        .with_double_buffering(true)
+        .with_quirks(quirks)
+        .build();
+    chipset.set_key_mask(!(1 << 0x3));
+
+    let rom = Rom::from_bytes("FORK", &[0x01, 0x23, 0x60, 0x2A]);
+    let mut fork = chipset.fork_with_rom(rom).unwrap();
+    assert_eq!("FORK", fork.chipset().rom.get_name());
+    assert_eq!(quirks, fork.chipset().quirks);
+    assert_eq!(!(1 << 0x3), fork.chipset().key_mask);
+    assert_eq!(4, fork.chipset().pc_history_capacity);
+    assert!(fork.chipset().front_display.is_some());
+    assert_eq!(Ok(Operation::None), fork.step());
+    assert_eq!(Ok(Operation::None), fork.step());
+    assert_eq!(0x2A, fork.chipset().registers[0]);
+
+    // the keyboard is shared
+    fork.set_key(0x5, true);
+    assert!(chipset.chipset().keyboard.read().get_keys()[0x5]);
+
+    let rom = Rom::from_bytes("LARGE", &[0; memory::SIZE]);
+    assert!(matches!(
+        chipset.fork_with_rom(rom),
+        Err(ChipError::RomSize { .. })
+    ));
+}
+
+#[test]
+/// test the conversion of the display into a RGBA buffer
+fn test_display_rgba() {
+    let fg = [0xFF, 0xFF, 0xFF, 0xFF];
+    let bg = [0x00, 0x00, 0x00, 0xFF];
+    let mut chipset = get_default_chip();
+    chipset.set_pixel(1, 0, true).unwrap();
+    chipset.set_pixel(0, 1, true).unwrap();
+
+    let rgba = chipset.display_rgba(fg, bg);
+    assert_eq!(display::RESOLUTION * 4, rgba.len());
+    let pixel = |x: usize, y: usize| &rgba[(y * display::HEIGHT + x) * 4..][..4];
+    assert_eq!(bg, pixel(0, 0));
+    assert_eq!(fg, pixel(1, 0));
+    assert_eq!(fg, pixel(0, 1));
+    assert_eq!(bg, pixel(1, 1));
+}
+
+#[test]
+/// test the detection of a program, that has ended
+fn test_is_halted() {
+    let pc = cpu::PROGRAM_COUNTER;
+    let mut chipset = get_default_chip().with_opcode_at(pc, 0x1202);
+    assert!(!chipset.is_halted());
+
+    chipset.write_opcode_at(pc, 0x1200);
+    assert!(chipset.is_halted());
+    assert_eq!(Ok(Operation::None), chipset.step());
+    assert!(chipset.is_halted());
+}
+
+#[test]
+/// test changing the quirks of a running chip
+fn test_set_quirks() {
+    let pc = cpu::PROGRAM_COUNTER;
+    // FX75 twice, once with each mode
+    let mut chipset = get_default_chip()
+        .with_opcode_at(pc, 0xF075)
+        .with_opcode_at(pc + memory::opcodes::SIZE, 0x1200);
+    assert_eq!(&Quirks::default(), chipset.quirks());
+
+    let quirks = Quirks {
+        mode: opcode::Mode::SChip,
+        ..Default::default()
+    };
+    chipset.set_quirks(quirks);
+    assert_eq!(&quirks, chipset.quirks());
+    assert_eq!(Ok(Operation::None), chipset.step());
+    assert_eq!(Ok(Operation::None), chipset.step());
+
+    // the cached opcode is checked against the new mode
+    chipset.set_quirks(Quirks::default());
+    assert_eq!(
+        Err(ProcessError::Opcode(OpcodeError::InvalidOpcode(0xF075))),
+        chipset.step()
+    );
+}
+
+#[test]
+/// test setting up the registers from the outside
+fn test_registers_mut() {
+    let pc = cpu::PROGRAM_COUNTER;
+    // ADD V1, V2
+    let mut chipset = get_default_chip().with_opcode_at(pc, 0x8124);
+    let registers = chipset.registers_mut();
+    assert_eq!(cpu::register::SIZE, registers.len());
+    registers[1] = 0xF0;
+    registers[2] = 0x20;
+
+    assert!(chipset.step().is_ok());
+    assert_eq!(0x10, chipset.chipset().registers[1]);
+    assert_eq!(1, chipset.chipset().registers[cpu::register::LAST]);
+}
+
+#[test]
+/// test the introspection of the next opcode
+fn test_current_instruction() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+    chipset.write_opcode_at(pc, 0x00E0);
+    chipset.write_opcode_at(pc + 2, 0x0001);
+
+    assert_eq!(Ok(0x00E0), chipset.current_opcode());
+    assert_eq!(
+        Ok(Opcodes::Zero(opcode::Zero::Clear)),
+        chipset.current_instruction()
+    );
+    // nothing was executed or cached
+    assert_eq!(pc, chipset.chipset().program_counter);
+    assert!(chipset.chipset().opcode_memory.is_empty());
+
+    assert!(chipset.step().is_ok());
+    assert_eq!(Ok(0x0001), chipset.current_opcode());
+    assert_eq!(
+        Err(OpcodeError::InvalidOpcode(0x0001)),
+        chipset.current_instruction()
+    );
+}
+
+#[test]
+/// test decoding and formatting the opcode at an arbitrary address
+fn test_instruction_at() {
+    let mut chipset = get_default_chip();
+    chipset.write_opcode_at(0x300, 0xD125);
+    chipset.write_opcode_at(0x302, 0x0123);
+    chipset.write_opcode_at(0x304, 0x00FD);
+
+    assert_eq!(
+        Ok((
+            Opcodes::D(opcode::Thirteen { x: 1, y: 2, n: 5 }),
+            "DRW V1, V2, 5".to_string()
+        )),
+        chipset.instruction_at(0x300)
+    );
+    assert_eq!(
+        Err(OpcodeError::InvalidOpcode(0x0123)),
+        chipset.instruction_at(0x302)
+    );
+    // the exit is only supported by the SuperChip
+    assert_eq!(
+        Err(OpcodeError::InvalidOpcode(0x00FD)),
+        chipset.instruction_at(0x304)
+    );
+    chipset.chipset_mut().quirks.mode = opcode::Mode::SChip;
+    assert_eq!(
+        Ok((Opcodes::Zero(opcode::Zero::Exit), "EXIT".to_string())),
+        chipset.instruction_at(0x304)
+    );
+    assert!(matches!(
+        chipset.instruction_at(memory::SIZE - 1),
+        Err(OpcodeError::MemoryInvalid { .. })
+    ));
+}
+
+#[test]
+/// test the report of a single step
+fn test_debug_step() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+    chipset.write_opcode_at(pc, 0x00E0);
+    chipset.write_opcode_at(pc + 2, 0x0001);
+    chipset.chipset_mut().delay_timer.set_value(0);
+    chipset.chipset_mut().sound_timer.set_value(0);
+    let registers = chipset.chipset().registers;
+
+    assert_eq!(
+        Ok(StepReport {
+            opcode: 0x00E0,
+            instruction: Some(Opcodes::Zero(opcode::Zero::Clear)),
+            operation: Operation::Clear,
+            program_counter: pc + 2,
+            registers,
+            delay_timer: 0,
+            sound_timer: 0,
+        }),
+        chipset.debug_step()
+    );
+    assert_eq!(
+        Err(ProcessError::Opcode(OpcodeError::InvalidOpcode(0x0001))),
+        chipset.debug_step()
+    );
+    assert_eq!(pc + 2, chipset.chipset().program_counter);
+}
+
+#[test]
+/// test the report of a step over a skipped `0NNN` machine code call
+fn test_debug_step_sys_call() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+    chipset.set_quirks(Quirks {
+        sys_call: SysCall::Ignore,
+        ..Default::default()
+    });
+    chipset.write_opcode_at(pc, 0x0123);
+
+    let report = chipset.debug_step().unwrap();
+    assert_eq!(0x0123, report.opcode);
+    assert_eq!(None, report.instruction);
+    assert_eq!(Operation::None, report.operation);
+    assert_eq!(pc + 2, report.program_counter);
+    assert_eq!(pc + 2, chipset.chipset().program_counter);
+}
+
+#[test]
+/// test the deadline and cycle limit of running
+fn test_run_until() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+
+    // jump onto itself forever
+    chipset.write_opcode_at(pc, 0x1000 ^ pc as Opcode);
+
+    let now = web_time::Instant::now();
+    assert_eq!(Ok(RunStop::Deadline), chipset.run_until(now, 10));
+    assert_eq!(pc, chipset.chipset().program_counter);
+
+    let deadline = now + std::time::Duration::from_secs(60);
+    assert_eq!(Ok(RunStop::CycleLimit), chipset.run_until(deadline, 10));
+    assert_eq!(pc, chipset.chipset().program_counter);
+
+    chipset.write_opcode_at(pc, 0xF00A);
+    assert_eq!(Ok(RunStop::Wait), chipset.run_until(deadline, 10));
+}
+
+#[test]
+/// test stopping the execution on a write to a watched address
+fn test_run_until_break() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER;
+
+    // LD I, 0x300; LD B, V0; LD [I], V1; JP 0x206
+    chipset.write_opcode_at(pc, 0xA300);
+    chipset.write_opcode_at(pc + 2, 0xF033);
+    chipset.write_opcode_at(pc + 4, 0xF155);
+    chipset.write_opcode_at(pc + 6, 0x1206);
+
+    // nothing watched
+    assert_eq!(Ok(RunStop::CycleLimit), chipset.run_until_break(2));
+
+    chipset.chipset_mut().program_counter = pc;
+    chipset.add_write_watch(0x302);
+    assert_eq!(
+        Ok(RunStop::WriteWatch { addr: 0x302 }),
+        chipset.run_until_break(10)
+    );
+    assert_eq!(pc + 4, chipset.chipset().program_counter);
+
+    // the registers are stored at 0x300 and 0x301
+    assert!(chipset.remove_write_watch(0x302));
+    assert!(!chipset.remove_write_watch(0x302));
+    chipset.add_write_watch(0x301);
+    assert_eq!(
+        Ok(RunStop::WriteWatch { addr: 0x301 }),
+        chipset.run_until_break(10)
+    );
+    assert_eq!(pc + 6, chipset.chipset().program_counter);
+
+    // the loop does not write anything
+    assert_eq!(Ok(RunStop::CycleLimit), chipset.run_until_break(10));
+}
+
+#[test]
+/// test receiving the events of the subscribed kinds
+fn test_subscribe() {
+    let mut chipset = get_default_chip();
+    chipset.chipset_mut().quirks.mode = opcode::Mode::SChip;
+    let pc = cpu::PROGRAM_COUNTER;
+
+    // LD V0, 5; LD ST, V0; LD I, 0x300; LD [I], V0; DRW V0, V0, 1; CLS; EXIT
+    chipset.write_opcode_at(pc, 0x6005);
+    chipset.write_opcode_at(pc + 2, 0xF018);
+    chipset.write_opcode_at(pc + 4, 0xA300);
+    chipset.write_opcode_at(pc + 6, 0xF055);
+    chipset.write_opcode_at(pc + 8, 0xD001);
+    chipset.write_opcode_at(pc + 10, 0x00E0);
+    chipset.write_opcode_at(pc + 12, 0x00FD);
+    chipset.add_write_watch(0x300);
+
+    let draws = chipset.subscribe(EventKind::Draw);
+    let beeps = chipset.subscribe(EventKind::Beep);
+    let breakpoints = chipset.subscribe(EventKind::Breakpoint);
+    let halts = chipset.subscribe(EventKind::Halt);
+    // a dropped subscription is removed on the next event
+    drop(chipset.subscribe(EventKind::Draw));
+    // the writes of the test helpers are not reported
+    chipset.write_opcode_at(0x300, 0xABCD);
+
+    for _ in 0..7 {
+        assert!(chipset.step().is_ok());
+    }
+    // an exited program does not halt again
+    assert_eq!(Ok(Operation::Exit), chipset.step());
+
+    let received =
+        |receiver: std::sync::mpsc::Receiver<Event>| receiver.try_iter().collect::<Vec<_>>();
+    // clearing the display changes it as well
+    assert_eq!(vec![Event::Draw, Event::Draw], received(draws));
+    assert_eq!(vec![Event::Beep { duration: 5 }], received(beeps));
+    assert_eq!(
+        vec![Event::Breakpoint { addr: 0x300 }],
+        received(breakpoints)
+    );
+    assert_eq!(vec![Event::Halt], received(halts));
+    assert_eq!(4, chipset.chipset().subscribers.len());
+}
+
+#[test]
+/// test addressing registers outside of a reduced register bank
+fn test_register_count() {
+    let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+        .with_register_count(8)
+        .build();
+    let pc = cpu::PROGRAM_COUNTER;
+
+    // LD V7, 0x55; LD V8, 0x55; ADD V1, V9; JP 0x200
+    chipset.write_opcode_at(pc, 0x6755);
+    chipset.write_opcode_at(pc + 2, 0x6855);
+    chipset.write_opcode_at(pc + 4, 0x8194);
+    chipset.write_opcode_at(pc + 6, 0x1200);
+
+    assert_eq!(Ok(Operation::None), chipset.step());
+    assert_eq!(0x55, chipset.registers_array()[7]);
+    assert_eq!(Err(ProcessError::InvalidRegister(8)), chipset.step());
+    assert_eq!(pc + 2, chipset.chipset().program_counter);
+
+    chipset.chipset_mut().program_counter = pc + 4;
+    assert_eq!(Err(ProcessError::InvalidRegister(9)), chipset.step());
+
+    // a jump has no register operands
+    chipset.chipset_mut().program_counter = pc + 6;
+    assert_eq!(Ok(Operation::None), chipset.step());
+
+    let fork = chipset.fork_with_rom(get_base()).unwrap();
+    assert_eq!(8, fork.chipset().register_count);
+}
+
+#[test]
+#[should_panic(expected = "The register bank requires between 1 and 16 registers.")]
+fn test_register_count_too_large() {
+    let _: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+        .with_register_count(cpu::register::SIZE + 1)
+        .build();
+}
+
+#[test]
+/// test executing an assembled snippet at the program counter
+fn test_execute_str() {
+    let mut chipset: ChipSet<Worker, NoCallback> = ChipSet::new(get_base());
+    let pc = 0x300;
+    chipset.chipset_mut().program_counter = pc;
+
+    let snippet = "
+            LD V0, 5
+            LD V1, 0
+        loop:
+            ADD V1, 2
+            SE V1, 6
+            JP loop
+            ADD V0, V1
+    ";
+    assert_eq!(Ok(()), chipset.execute_str(snippet));
+    assert_eq!(11, chipset.registers_mut()[0]);
+    assert_eq!(pc + 12, chipset.chipset().program_counter);
+
+    // the snippet starts at the new program counter
+    assert_eq!(Ok(()), chipset.execute_str("LD V2, 1"));
+    assert_eq!(1, chipset.registers_mut()[2]);
+    assert_eq!(pc + 14, chipset.chipset().program_counter);
+
+    assert_eq!(Ok(()), chipset.execute_str("LD V3, K"));
+    assert_eq!(pc + 14, chipset.chipset().program_counter);
+    chipset.reset(false);
+
+    assert_eq!(
+        Err(ProcessError::BudgetExceeded),
+        chipset.execute_str("loop: JP loop")
+    );
+}
+
+#[test]
+/// test the amount of program bytes after the program counter
+fn test_remaining_program_bytes() {
+    let rom = Rom::from_bytes("test", &[0x00, 0xE0, 0x12, 0x00]);
+    let mut chipset: ChipSet<Worker, NoCallback> = ChipSet::new(rom);
+    assert_eq!(4, chipset.remaining_program_bytes());
+
+    chipset.chipset_mut().program_counter = cpu::PROGRAM_COUNTER + 2;
+    assert_eq!(2, chipset.remaining_program_bytes());
+
+    // outside of the program
+    chipset.chipset_mut().program_counter = 0x100;
+    assert_eq!(4, chipset.remaining_program_bytes());
+    chipset.chipset_mut().program_counter = 0x300;
+    assert_eq!(0, chipset.remaining_program_bytes());
+}
+
+#[test]
+/// test transforming the keyboard before the opcodes read it
+fn test_input_filter() {
+    let mut chipset = get_default_chip();
+    let pc = chipset.chipset().program_counter;
+    chipset.registers_mut()[0] = 2;
+    // SKP V0
+    chipset.write_opcode_at(pc, 0xE09E);
+    chipset.set_key(1, true);
+
+    // remap the key 1 onto the key 2
+    chipset.set_input_filter(|keys| (keys & 0b10) << 1);
+    assert_eq!(Ok(Operation::None), chipset.step());
+    assert_eq!(pc + 4, chipset.chipset().program_counter);
+    assert!(!chipset.chipset().is_key_pressed(1));
+
+    chipset.clear_input_filter();
+    chipset.chipset_mut().program_counter = pc;
+    assert_eq!(Ok(Operation::None), chipset.step());
+    assert_eq!(pc + 2, chipset.chipset().program_counter);
+    assert!(chipset.chipset().is_key_pressed(1));
+}
+
+#[test]
+/// test packing and unpacking the display
+fn test_export_import_display() {
+    let mut chipset = get_default_chip();
+    let chip = chipset.chipset_mut();
+    chip.display[0][0] = true;
+    chip.display[0][9] = true;
+    chip.display[display::WIDTH - 1][display::HEIGHT - 1] = true;
+
+    let data = chipset.export_display();
+    assert_eq!(display::RESOLUTION / 8, data.len());
+    assert_eq!([0x80, 0x40, 0x00], data[..3]);
+    assert_eq!(Some(&0x01), data.last());
+
+    let mut other = get_default_chip();
+    assert_eq!(Ok(()), other.import_display(&data));
+    assert_eq!(chipset.get_display(), other.get_display());
+
+    assert_eq!(
+        Err(ProcessError::DisplaySize {
+            size: 3,
+            expected: display::RESOLUTION / 8
+        }),
+        other.import_display(&data[..3])
+    );
+}
+
+#[test]
+/// test executing an opcode at an odd address
+fn test_enforce_alignment() {
+    let mut chipset = get_default_chip();
+    let pc = cpu::PROGRAM_COUNTER + 1;
+    chipset.write_opcode_at(pc, 0x6042);
+    chipset.chipset_mut().program_counter = pc;
+
+    assert!(chipset.quirks().enforce_alignment);
+    assert_eq!(
+        Err(ProcessError::MisalignedProgramCounter(pc)),
+        chipset.step()
+    );
+
+    chipset.set_quirks(Quirks {
+        enforce_alignment: false,
+        ..Default::default()
+    });
+    assert_eq!(Ok(Operation::None), chipset.step());
+    assert_eq!(0x42, chipset.registers_mut()[0]);
+    assert_eq!(pc + 2, chipset.chipset().program_counter);
+}
+
+#[test]
+/// test reading and writing the registers as array
+fn test_registers_array() {
+    let mut chipset = get_default_chip();
+    let registers = chipset.chipset().registers;
+    assert_eq!(registers, chipset.registers_array());
+
+    let mut registers = [0; cpu::register::SIZE];
+    registers[0xA] = 0x42;
+    chipset.set_registers_array(registers);
+    assert_eq!(registers, chipset.registers_array());
+    assert_eq!(0x42, chipset.registers_mut()[0xA]);
+}
+
+#[test]
+/// test running a rom to its resulting display
+fn test_run_to_display() {
+    let pc = cpu::PROGRAM_COUNTER;
+    // LD F, V0; DRW V1, V2, 5; LD V3, K; CLS
+    let mut chipset: ChipSet<Worker, NoCallback> =
+        ChipSet::with_registers(get_base(), [0; cpu::register::SIZE])
+            .with_opcode_at(pc, 0xF029)
+            .with_opcode_at(pc + 2, 0xD125)
+            .with_opcode_at(pc + 4, 0xF30A)
+            .with_opcode_at(pc + 6, 0x00E0);
+
+    let frame = chipset.run_to_display(1).unwrap();
+    assert!(!frame.rows().flatten().any(|&pixel| pixel));
+
+    // the wait stops the execution before the display is cleared
+    let frame = chipset.run_to_display(10).unwrap();
+    assert_eq!(Some(true), frame.get(0, 0));
+    assert_eq!(Some(false), frame.get(1, 1));
+    assert_eq!(pc + 4, chipset.chipset().program_counter);
+}
+
+#[test]
+#[cfg(feature = "instrument")]
+/// test the hooks around the execution of every opcode
+fn test_execute_hooks() {
+    let pc = cpu::PROGRAM_COUNTER;
+    let mut chipset = get_default_chip()
+        .with_opcode_at(pc, 0x00E0)
+        .with_opcode_at(pc + 2, 0x0001);
+    let calls = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let before = calls.clone();
+    chipset.set_before_execute(Box::new(move |opcode| {
+        before.lock().push((*opcode, None));
+    }));
+    let after = calls.clone();
+    chipset.set_after_execute(Box::new(move |opcode, operation| {
+        after.lock().push((*opcode, Some(*operation)));
+    }));
+
+    assert_eq!(Ok(Operation::Clear), chipset.step());
+    // the unsupported opcode is never executed
+    assert!(chipset.step().is_err());
+
+    let clear = Opcodes::Zero(opcode::Zero::Clear);
+    assert_eq!(
+        vec![(clear, None), (clear, Some(Operation::Clear))],
+        *calls.lock()
+    );
+}
+
+#[test]
+/// test the incremental checksum of the memory
+fn test_memory_checksum() {
+    let pc = cpu::PROGRAM_COUNTER;
+    let mut chipset = get_default_chip();
+    let initial = chipset.memory_checksum();
+    assert_eq!(initial, get_default_chip().memory_checksum());
+
+    // LD B, V0; LD [I], V1
+    chipset.write_opcode_at(pc, 0xF033);
+    chipset.write_opcode_at(pc + 2, 0xF155);
+    let written = chipset.memory_checksum();
+    assert_ne!(initial, written);
+
+    let chip = chipset.chipset_mut();
+    chip.index_register = 0x400;
+    chip.registers[0] = 123;
+    assert!(chipset.step().is_ok());
+    let bcd = chipset.memory_checksum();
+    assert_ne!(written, bcd);
+    assert!(chipset.step().is_ok());
+    assert_ne!(bcd, chipset.memory_checksum());
+
+    // the checksum only depends on the content of the memory
+    let snapshot = chipset.snapshot();
+    let restored: ChipSet<Worker, NoCallback> = ChipSet::from_snapshot(snapshot).unwrap();
+    assert_eq!(chipset.memory_checksum(), restored.memory_checksum());
+
+    chipset.reset(false);
+    assert_eq!(initial, chipset.memory_checksum());
+}
+
+#[test]
+/// test setting single pixels of the display
+fn test_set_pixel() {
+    let mut chipset = get_default_chip();
+    assert_eq!(Ok(()), chipset.set_pixel(63, 31, true));
+    assert_eq!(Some(true), chipset.frame().get(63, 31));
+    assert_eq!(Ok(()), chipset.set_pixel(63, 31, false));
+    assert_eq!(Some(false), chipset.frame().get(63, 31));
+
+    assert_eq!(
+        Err(ProcessError::PixelOutOfBounds { x: 64, y: 0 }),
+        chipset.set_pixel(64, 0, true)
+    );
+    assert_eq!(
+        Err(ProcessError::PixelOutOfBounds { x: 0, y: 32 }),
+        chipset.set_pixel(0, 32, true)
+    );
+
+    // a pixel set before drawing collides
+    let pc = cpu::PROGRAM_COUNTER;
+    let mut chipset: ChipSet<Worker, NoCallback> =
+        ChipSet::with_registers(get_base(), [0; cpu::register::SIZE]).with_opcode_at(pc, 0xD011);
+    chipset.chipset_mut().index_register = display::fontset::LOCATION;
+    chipset.set_pixel(1, 0, true).unwrap();
+    assert_eq!(Ok(Operation::Draw), chipset.step());
+    assert_eq!(1, chipset.chipset().registers[cpu::register::LAST]);
+    assert_eq!(Some(false), chipset.frame().get(1, 0));
+}
+
+#[test]
+#[cfg(feature = "trace")]
+/// test the log of the memory writes
+fn test_take_write_log() {
+    use super::MemWrite;
+
+    let pc = cpu::PROGRAM_COUNTER;
+    let mut chipset = get_default_chip()
+        .with_opcode_at(pc, 0xA300)
+        .with_opcode_at(pc + 2, 0xF033)
+        .with_opcode_at(pc + 4, 0xF155);
+    chipset.registers_mut()[..2].copy_from_slice(&[123, 4]);
+    for _ in 0..3 {
+        assert!(chipset.step().is_ok());
+    }
+
+    let write = |pc, opcode, addr, value| MemWrite {
+        pc,
+        opcode,
+        addr,
+        value,
+    };
+    assert_eq!(
+        vec![
+            write(pc + 2, 0xF033, 0x300, 1),
+            write(pc + 2, 0xF033, 0x301, 2),
+            write(pc + 2, 0xF033, 0x302, 3),
+            write(pc + 4, 0xF155, 0x300, 123),
+            write(pc + 4, 0xF155, 0x301, 4),
+        ],
+        chipset.take_write_log()
+    );
+    assert!(chipset.take_write_log().is_empty());
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+/// test the coverage report of the executed opcodes
+fn test_executed_opcode_kinds() {
+    let pc = cpu::PROGRAM_COUNTER;
+    // CLS; LD V1, 2; LD V2, 3; CLS; unsupported
+    let mut chipset = get_default_chip()
+        .with_opcode_at(pc, 0x00E0)
+        .with_opcode_at(pc + 2, 0x6102)
+        .with_opcode_at(pc + 4, 0x6203)
+        .with_opcode_at(pc + 6, 0x00E0)
+        .with_opcode_at(pc + 8, 0x0001);
+    assert!(chipset.executed_opcode_kinds().is_empty());
+
+    for _ in 0..4 {
+        assert!(chipset.step().is_ok());
+    }
+    assert!(chipset.step().is_err());
+
+    let mut kinds: Vec<_> = chipset.executed_opcode_kinds().iter().copied().collect();
+    kinds.sort_unstable();
+    assert_eq!(vec!["00E0", "6XNN"], kinds);
+
+    chipset.reset(false);
+    assert!(chipset.executed_opcode_kinds().is_empty());
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+/// test the counters of the executed opcodes
+fn test_stats() {
+    use super::Stats;
+
+    let pc = cpu::PROGRAM_COUNTER;
+    // CALL 0x206; DRW V0, V0, 5 (at 0x206); JP 0x200
+    let mut chipset = get_default_chip()
+        .with_opcode_at(pc, 0x2206)
+        .with_opcode_at(pc + 6, 0xD005)
+        .with_opcode_at(pc + 8, 0x1200);
+    chipset.chipset_mut().index_register = display::fontset::LOCATION;
+    // the sprite must not be clipped by an edge of the display
+    chipset.chipset_mut().registers[0] = 0;
+    assert_eq!(Stats::default(), chipset.stats());
+
+    for _ in 0..3 {
+        assert!(chipset.step().is_ok());
+    }
+    let pixels = display::fontset::FONTSET[..5]
+        .iter()
+        .map(|row| row.count_ones() as u64)
+        .sum();
+    assert_eq!(
+        Stats {
+            sprites_drawn: 1,
+            pixels_toggled: pixels,
+            subroutine_calls: 1,
+            jumps: 1,
+        },
+        chipset.stats()
+    );
+
+    chipset.reset(false);
+    assert_eq!(Stats::default(), chipset.stats());
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+/// test the execution counts of every address
+fn test_execution_profile() {
+    let pc = cpu::PROGRAM_COUNTER;
+    // LD V0, 1; JP 0x200
+    let mut chipset = get_default_chip()
+        .with_opcode_at(pc, 0x6001)
+        .with_opcode_at(pc + 2, 0x1200);
+    assert!(chipset.execution_profile().is_empty());
+
+    for _ in 0..5 {
+        assert!(chipset.step().is_ok());
+    }
+    let profile = chipset.execution_profile();
+    assert_eq!(2, profile.len());
+    assert_eq!(Some(&3), profile.get(&pc));
+    assert_eq!(Some(&2), profile.get(&(pc + 2)));
+
+    chipset.reset(false);
+    assert!(chipset.execution_profile().is_empty());
+}
+
+#[test]
+/// test skipping and rewinding single opcodes
+fn test_skip_and_rewind_instruction() {
+    let pc = cpu::PROGRAM_COUNTER;
+    let mut chipset = get_default_chip().with_opcode_at(pc, 0xF00A);
+    assert_eq!(
+        Err(ProcessError::MemoryOutOfBounds(pc - 2)),
+        chipset.rewind_instruction()
+    );
+
+    // the wait is cancelled by skipping it
+    assert_eq!(Ok(Operation::Wait), chipset.step());
+    assert_eq!(Ok(()), chipset.skip_instruction());
+    assert_eq!(pc + 2, chipset.chipset().program_counter);
+    assert!(chipset.chipset().preprocessor.is_none());
+
+    assert_eq!(Ok(()), chipset.rewind_instruction());
+    assert_eq!(pc, chipset.chipset().program_counter);
+
+    chipset.chipset_mut().program_counter = memory::SIZE - 2;
+    assert_eq!(
+        Err(ProcessError::MemoryOutOfBounds(memory::SIZE)),
+        chipset.skip_instruction()
+    );
+    assert_eq!(memory::SIZE - 2, chipset.chipset().program_counter);
+}
+
+#[test]
+/// test the generated square wave
+fn test_audio_samples() {
+    let mut chipset = get_default_chip();
+
+    chipset.chipset_mut().sound_timer.set_value(0);
+    assert_eq!(vec![0.0; 8], chipset.audio_samples(8800, 8));
+
+    // 20 samples per period at 8800Hz
+    chipset.chipset_mut().sound_timer.set_value(60);
+    let first = chipset.audio_samples(8800, 15);
+    let second = chipset.audio_samples(8800, 25);
+
+    let samples: Vec<_> = first.into_iter().chain(second).collect();
+    for (i, sample) in samples.into_iter().enumerate() {
+        let expected = if i % 20 < 10 {
+            sound::AMPLITUDE
+        } else {
+            -sound::AMPLITUDE
+        };
+        assert_eq!(expected, sample, "sample {}", i);
+    }
+}
+
+#[test]
+/// test the program counter history
+fn test_pc_history() {
+    let pc = cpu::PROGRAM_COUNTER;
+    let mut chipset: ChipSet<Worker, NoCallback> =
+        ChipSetBuilder::new(get_base()).with_pc_history(2).build();
+    chipset.write_opcode_at(pc, 0x6001);
+    chipset.write_opcode_at(pc + 2, 0x6002);
+    chipset.write_opcode_at(pc + 4, 0x1000 ^ pc as Opcode);
+
+    assert!(chipset.pc_history().is_empty());
+    for _ in 0..3 {
+        assert!(chipset.step().is_ok());
+    }
+    assert_eq!(&[pc + 2, pc + 4], chipset.pc_history());
+    assert!(chipset.step().is_ok());
+    assert_eq!(&[pc + 4, pc], chipset.pc_history());
+
+    // disabled by default
+    let mut chipset = get_default_chip();
+    assert!(chipset.step().is_ok());
+    assert!(chipset.pc_history().is_empty());
+}
+
+#[test]
+/// test a faster timer frequency
+fn test_timer_hz() {
+    let mut chipset: ChipSet<Worker, NoCallback> =
+        ChipSetBuilder::new(get_base()).with_timer_hz(1000).build();
+    let chip = chipset.chipset_mut();
+
+    chip.delay_timer.set_value(60);
+    chip.sound_timer.set_value(60);
+
+    // at 1000Hz the timers reach 0 after 60ms
+    std::thread::sleep(std::time::Duration::from_millis(300));
+
+    assert_eq!(0, chip.get_delay_timer());
+    assert_eq!(0, chip.get_sound_timer());
+}
+
+#[test]
+/// test the interval of the timers for a frequency
+fn test_timer_interval() {
+    use super::chipset::timer_interval;
+    use std::time::Duration;
+
+    assert_eq!(Duration::from_nanos(16_666_666), timer_interval(60));
+    assert_eq!(Duration::from_millis(1), timer_interval(1000));
+    assert_eq!(Duration::from_secs(1), timer_interval(1));
+}
+
+#[test]
+/// test the prediction of the cycles until the timers reach zero
+fn test_cycles_until_timer_zero() {
+    let mut chipset: ChipSet<Worker, NoCallback> =
+        ChipSetBuilder::new(get_base()).with_timer_hz(120).build();
+    chipset.pause();
+    let chip = chipset.chipset_mut();
+    chip.delay_timer.set_value(60);
+    chip.sound_timer.set_value(1);
+
+    // 60 ticks at 120Hz take half a second
+    assert_eq!(cpu::HERTZ / 2, chipset.cycles_until_delay_zero());
+    // a single tick at 120Hz takes 4.16 cycles at 500Hz
+    assert_eq!(5, chipset.cycles_until_sound_zero());
+
+    chipset.chipset_mut().sound_timer.set_value(0);
+    assert_eq!(0, chipset.cycles_until_sound_zero());
+}
+
+#[test]
+/// test setting the timers without executing an opcode
+fn test_set_timers() {
+    let mut chipset = get_default_chip();
+    let changes = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let cchanges = changes.clone();
+    chipset.on_sound_change(Box::new(move |active| cchanges.lock().push(active)));
+    chipset.pause();
+
+    chipset.set_delay_timer(42);
+    chipset.set_sound_timer(7);
+    assert_eq!(42, chipset.get_delay_timer());
+    assert_eq!(7, chipset.get_sound_timer());
+
+    // the timers are read by the opcodes
+    let pc = cpu::PROGRAM_COUNTER;
+    chipset.write_opcode_at(pc, 0xF307);
+    assert!(chipset.step().is_ok());
+    assert_eq!(42, chipset.registers_array()[3]);
+
+    // the timers keep counting down
+    chipset.resume();
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    assert_eq!(0, chipset.get_sound_timer());
+    assert!(chipset.get_delay_timer() < 42);
+    chipset.set_sound_timer(0);
+    assert_eq!(vec![true, false], *changes.lock());
+}
+
+#[test]
+/// test advancing the timers by multiple ticks at once
+fn test_tick_timers_by() {
+    let mut chipset = get_default_chip();
+    chipset.pause();
+    let changes = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let cchanges = changes.clone();
+    chipset.on_sound_change(Box::new(move |active| cchanges.lock().push(active)));
+    let chip = chipset.chipset_mut();
+    chip.delay_timer.set_value(200);
+    chip.sound_timer.set_value(3);
+
+    chipset.tick_timers_by(2);
+    assert_eq!(198, chipset.chipset().get_delay_timer());
+    assert_eq!(1, chipset.get_sound_timer());
+
+    chipset.tick_timers_by(1000);
+    assert_eq!(0, chipset.chipset().get_delay_timer());
+    assert_eq!(0, chipset.get_sound_timer());
+    assert_eq!(vec![true, false], *changes.lock());
+}
+
+#[test]
+/// test the view of the display
+fn test_frame() {
+    let mut chipset = get_default_chip();
+    chipset.chipset_mut().display[2][1] = true;
+    let frame = chipset.frame();
+
+    assert_eq!(display::HEIGHT, frame.width());
+    assert_eq!(display::WIDTH, frame.height());
+    assert_eq!(Some(true), frame.get(1, 2));
+    assert_eq!(Some(false), frame.get(2, 1));
+    assert_eq!(None, frame.get(frame.width(), 0));
+    assert_eq!(None, frame.get(0, frame.height()));
+    assert_eq!(frame.height(), frame.rows().count());
+    assert_eq!(Some(&chipset.get_display()[2][..]), frame.row(2));
+}
+
+#[test]
+/// test reading the font sprites
+fn test_font_sprite() {
+    let chipset = get_default_chip();
+    assert_eq!(&display::fontset::FONTSET[..], chipset.fontset());
+    assert_eq!(
+        Some(&[0xF0, 0x90, 0x90, 0x90, 0xF0][..]),
+        chipset.font_sprite(0x0)
+    );
+    assert_eq!(
+        Some(&[0xF0, 0x80, 0xF0, 0x80, 0x80][..]),
+        chipset.font_sprite(0xF)
+    );
+    assert_eq!(None, chipset.font_sprite(0x10));
+}
+
+#[test]
+/// test blanking the display from the outside
+fn test_clear_display() {
+    let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+        .with_double_buffering(true)
+        .build();
+    chipset.fill_display(true);
+    chipset.present();
+    chipset.chipset_mut().last_collision[0] = true;
+
+    chipset.clear_display();
+    assert!(chipset.get_display().iter().flatten().all(|&pixel| !pixel));
+    assert!(chipset
+        .chipset()
+        .display
+        .iter()
+        .flatten()
+        .all(|&pixel| !pixel));
+    assert!(chipset.last_collision().iter().all(|&pixel| !pixel));
+}
+
+#[test]
+/// test the comparison of the machine state of two chips
+fn test_chipset_eq() {
+    let pc = cpu::PROGRAM_COUNTER;
+    let mut chipset: ChipSet<Worker, NoCallback> =
+        ChipSet::new(get_base()).with_opcode_at(pc, 0x6A2A);
+    let mut other: ChipSet<Worker, NoCallback> =
+        ChipSet::new(get_base()).with_opcode_at(pc, 0x6A2A);
+    assert_eq!(chipset, other);
+
+    assert!(chipset.step().is_ok());
+    assert_ne!(chipset, other);
+    assert!(other.step().is_ok());
+    assert_eq!(chipset, other);
+
+    // the configuration is not compared
+    other.set_instruction_budget(Some(1));
+    assert_eq!(chipset, other);
+    other.set_pixel(0, 0, true).unwrap();
+    assert_ne!(chipset, other);
+}
+
+#[test]
+/// test resetting only the cpu
+fn test_warm_reset() {
+    let pc = cpu::PROGRAM_COUNTER;
+    // CALL 0x204; LD V0, 0x2A (at 0x204)
+    let mut chipset = get_default_chip()
+        .with_opcode_at(pc, 0x2204)
+        .with_opcode_at(pc + 4, 0x602A);
+    chipset.set_rpl_flags([1; cpu::RPL_FLAGS]);
+    chipset.set_pixel(0, 0, true).unwrap();
+    chipset.chipset_mut().write_memory(0xF00, &[0xAB]);
+    assert!(chipset.step().is_ok());
+    assert!(chipset.step().is_ok());
+
+    chipset.warm_reset();
+    let chip = chipset.chipset();
+    assert_eq!(pc, chip.program_counter);
+    assert_eq!([0; cpu::register::SIZE], chip.registers);
+    assert_eq!(0, chip.index_register);
+    assert!(chip.stack.is_empty());
+    assert_eq!(0xAB, chip.memory[0xF00]);
+    assert_eq!(&[0x60, 0x2A], &chip.memory[(pc + 4)..(pc + 6)]);
+    assert_eq!([1; cpu::RPL_FLAGS], chipset.get_rpl_flags());
+    assert_eq!(Some(true), chipset.frame().get(0, 0));
+
+    // the cold reset restores the memory
+    chipset.reset(false);
+    assert_eq!(0, chipset.chipset().memory[0xF00]);
+}
+
+#[test]
+/// test the comparison of two frames
+fn test_frame_diff() {
+    let mut chipset = get_default_chip();
+    chipset.chipset_mut().display[2][1] = true;
+    chipset.chipset_mut().display[0][3] = true;
+    let prev = chipset.get_display().to_vec();
+
+    chipset.chipset_mut().display[2][1] = false;
+    chipset.chipset_mut().display[4][5] = true;
+    let frame = chipset.frame();
+    let prev = Frame::new(&prev);
+
+    assert!(frame.diff(&frame).is_empty());
+    assert_eq!(vec![(1, 2, false), (5, 4, true)], frame.diff(&prev));
+    assert_eq!(vec![(1, 2, true), (5, 4, false)], prev.diff(&frame));
+
+    // pixels missing in the previous frame are off
+    let empty = Frame::new(&[]);
+    assert_eq!(vec![(3, 0, true), (5, 4, true)], frame.diff(&empty));
+}
+
 #[test]
 /// testing internal functionality of popping and pushing into the stack
 fn test_push_pop_stack() {
@@ -160,7 +1738,7 @@ mod zero {
         write_opcode_to_memory(&mut chip, pc, opcode);
 
         // run - if there was no panic it worked as intended
-        assert_eq!(chip.next(), Ok(Operation::Draw));
+        assert_eq!(chip.next(), Ok(Operation::Clear));
 
         assert_eq!(curr_pc + memory::opcodes::SIZE, chip.program_counter);
     }
@@ -207,6 +1785,78 @@ mod zero {
         write_opcode_to_memory(&mut chip, pc, opcode);
         assert_eq!(Err(OpcodeError::InvalidOpcode(opcode).into()), chip.next());
     }
+
+    #[test]
+    /// test scrolling the display
+    /// `0x00CN`, `0x00FB` and `0x00FC`
+    fn test_scroll_opcodes() {
+        let pc = cpu::PROGRAM_COUNTER;
+        let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+            .with_mode(opcode::Mode::SChip)
+            .build()
+            .with_opcode_at(pc, 0x00C3)
+            .with_opcode_at(pc + 2, 0x00FB)
+            .with_opcode_at(pc + 4, 0x00FC)
+            .with_opcode_at(pc + 6, 0x00FC);
+        let last = display::HEIGHT - 1;
+        chipset.set_pixel(1, 0, true).unwrap();
+        chipset.set_pixel(last, 1, true).unwrap();
+
+        assert_eq!(Ok(Operation::Scroll { dx: 0, dy: 3 }), chipset.step());
+        assert_eq!(Some(true), chipset.frame().get(1, 3));
+        assert_eq!(Some(true), chipset.frame().get(last, 4));
+        assert_eq!(Some(false), chipset.frame().get(1, 0));
+
+        // the pixels scrolled out of the display are lost
+        assert_eq!(Ok(Operation::Scroll { dx: 4, dy: 0 }), chipset.step());
+        assert_eq!(Some(true), chipset.frame().get(5, 3));
+        assert_eq!(Some(false), chipset.frame().get(3, 4));
+        assert_eq!(Some(false), chipset.frame().get(last, 4));
+
+        assert_eq!(Ok(Operation::Scroll { dx: -4, dy: 0 }), chipset.step());
+        assert_eq!(Some(true), chipset.frame().get(1, 3));
+        assert_eq!(Ok(Operation::Scroll { dx: -4, dy: 0 }), chipset.step());
+        assert!(chipset.frame().rows().flatten().all(|&pixel| !pixel));
+
+        // only supported by the superchip
+        let mut chipset = get_default_chip().with_opcode_at(pc, 0x00C3);
+        assert_eq!(
+            Err(OpcodeError::InvalidOpcode(0x00C3).into()),
+            chipset.step()
+        );
+    }
+
+    #[test]
+    /// test exiting the interpreter
+    /// `0x00FD`
+    fn test_exit_opcode() {
+        let pc = cpu::PROGRAM_COUNTER;
+        let opcode = 0x00FD;
+
+        // only supported by the superchip
+        let mut chipset = get_default_chip().with_opcode_at(pc, opcode);
+        assert_eq!(
+            Err(OpcodeError::InvalidOpcode(opcode).into()),
+            chipset.step()
+        );
+        assert!(!chipset.is_halted());
+
+        let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+            .with_mode(opcode::Mode::SChip)
+            .build()
+            .with_opcode_at(pc, opcode);
+        assert_eq!(Ok(Operation::Exit), chipset.step());
+        assert!(chipset.is_halted());
+
+        // nothing is executed anymore
+        chipset.write_opcode_at(pc, 0x6001);
+        assert_eq!(Ok(Operation::Exit), chipset.step());
+        assert_eq!(pc, chipset.chipset().program_counter);
+        assert_eq!(0, chipset.chipset().registers[0]);
+
+        chipset.reset(false);
+        assert!(!chipset.is_halted());
+    }
 }
 
 mod one {
@@ -695,6 +2345,46 @@ mod eight {
         assert_eq!(chip.program_counter, curr_pc + 1 * memory::opcodes::SIZE);
     }
 
+    #[test]
+    // 8XY5 and 8XY7
+    // VF is set to 0 when there's a borrow, and 1 when there isn't, this includes
+    // subtracting 0 and equal values.
+    fn test_substraction_borrow_flag() {
+        // (command, VX, VY, VX after, VF after)
+        let cases: [(Opcode, u8, u8, u8, u8); 10] = [
+            // VX - VY
+            (0x5, 0x14, 0xFA, 0x1A, 0),
+            (0x5, 0x00, 0x01, 0xFF, 0),
+            (0x5, 0xFA, 0x14, 0xE6, 1),
+            (0x5, 0x14, 0x00, 0x14, 1),
+            (0x5, 0x14, 0x14, 0x00, 1),
+            // VY - VX
+            (0x7, 0xFA, 0x14, 0x1A, 0),
+            (0x7, 0x01, 0x00, 0xFF, 0),
+            (0x7, 0x14, 0xFA, 0xE6, 1),
+            (0x7, 0x00, 0x14, 0x14, 1),
+            (0x7, 0x14, 0x14, 0x00, 1),
+        ];
+
+        for (command, vx, vy, result, flag) in cases {
+            let mut chipset = get_default_chip();
+            let chip = chipset.chipset_mut();
+            chip.registers[0x1] = vx;
+            chip.registers[0x2] = vy;
+
+            let opcode: Opcode = 0x8120 ^ command;
+            assert_eq!(Ok(Operation::None), chip.calc(&opcode.try_into().unwrap()));
+            assert_eq!(
+                (result, flag),
+                (chip.registers[0x1], chip.registers[cpu::register::LAST]),
+                "{:#06X} with VX={:#04X} and VY={:#04X}",
+                opcode,
+                vx,
+                vy
+            );
+        }
+    }
+
     #[test]
     // 8XYE
     // Stores the most significant bit of VX in VF and then shifts VX to the left by 1.
@@ -864,6 +2554,29 @@ mod b {
 
         assert_eq!(chip.program_counter, (addr + offset as u16) as usize);
     }
+
+    #[test]
+    /// BNNN
+    /// A target past the end of the memory is an error.
+    fn test_jump_to_nnn_with_offset_out_of_bounds() {
+        let mut chipset = get_default_chip();
+        let chip = chipset.chipset_mut();
+
+        chip.registers[0] = 0xFF;
+
+        let addr = 0xFFF;
+        let opcode: Opcode = 0xB << (3 * 4) ^ addr;
+
+        let pc = chip.program_counter;
+
+        write_opcode_to_memory(chip, pc, opcode);
+
+        assert_eq!(
+            chip.next(),
+            Err(ProcessError::MemoryOutOfBounds(0xFF + addr as usize))
+        );
+        assert_eq!(chip.program_counter, pc);
+    }
 }
 
 mod c {
@@ -898,7 +2611,212 @@ mod c {
     }
 }
 
-mod d {}
+mod d {
+    use super::*;
+    use crate::definitions::display;
+
+    #[test]
+    /// DXY0
+    /// A sprite without rows draws nothing and reports no collision.
+    fn test_draw_zero_rows() {
+        for mode in [opcode::Mode::Chip8, opcode::Mode::SChip] {
+            let mut chipset = get_default_chip();
+            let chip = chipset.chipset_mut();
+            let pc = chip.program_counter;
+            chip.quirks.mode = mode;
+            chip.index_register = display::fontset::LOCATION;
+            chip.registers[cpu::register::LAST] = 1;
+            chip.display[0][0] = true;
+            let display = chip.display.clone();
+            write_opcode_to_memory(chip, pc, 0xD000);
+
+            assert_eq!(chip.next(), Ok(Operation::None));
+            assert_eq!(display, chip.display);
+            assert_eq!(0, chip.registers[cpu::register::LAST]);
+            assert_eq!(pc + memory::opcodes::SIZE, chip.program_counter);
+        }
+    }
+
+    #[test]
+    /// DXYN
+    /// Overlapping sprites are combined according to the draw mode.
+    fn test_draw_modes() {
+        let (t, f) = (true, false);
+        // (mode, first pixels of the row after both draws, VF)
+        let cases = [
+            (DrawMode::Xor, [f, t, t, f, f, f, f, f, t], 1),
+            (DrawMode::Or, [t, t, t, t, f, f, f, f, t], 0),
+            (DrawMode::Overwrite, [t, f, f, t, f, f, f, f, t], 0),
+        ];
+
+        for (mode, pixels, flag) in cases {
+            let mut chipset = get_default_chip();
+            let chip = chipset.chipset_mut();
+            let pc = chip.program_counter;
+            chip.quirks.draw_mode = mode;
+            chip.registers[0] = 0;
+            // a pixel right of the sprites is kept in every mode
+            chip.display[0][8] = true;
+
+            // the first (0xF0) and the second (0x90) row of the `0` font
+            chip.index_register = display::fontset::LOCATION;
+            write_opcode_to_memory(chip, pc, 0xD001);
+            assert_eq!(chip.next(), Ok(Operation::Draw));
+            chip.index_register = display::fontset::LOCATION + 1;
+            write_opcode_to_memory(chip, pc + 2, 0xD001);
+            assert_eq!(chip.next(), Ok(Operation::Draw));
+
+            assert_eq!(pixels, chip.display[0][..9], "{:?}", mode);
+            assert_eq!(flag, chip.registers[0xF], "{:?}", mode);
+        }
+    }
+
+    #[test]
+    /// DXYN
+    /// The collided pixels of the last draw are recorded.
+    fn test_draw_last_collision() {
+        let mut chipset = get_default_chip();
+        let chip = chipset.chipset_mut();
+        let pc = chip.program_counter;
+
+        // draw the first row of the `0` font (0xF0) at (2, 1)
+        chip.index_register = display::fontset::LOCATION;
+        chip.registers[0] = 2;
+        chip.registers[1] = 1;
+        chip.display[1][3] = true;
+        chip.display[1][10] = true;
+        write_opcode_to_memory(chip, pc, 0xD011);
+        write_opcode_to_memory(chip, pc + 2, 0xD011);
+
+        assert_eq!(chip.next(), Ok(Operation::Draw));
+        let collided: Vec<_> = chipset
+            .last_collision()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &pixel)| pixel.then_some(i))
+            .collect();
+        assert_eq!(vec![display::HEIGHT + 3], collided);
+
+        // only the collisions of the latest draw are kept
+        assert_eq!(chipset.chipset_mut().next(), Ok(Operation::Draw));
+        assert_eq!(3, chipset.last_collision().iter().filter(|&&p| p).count());
+    }
+
+    #[test]
+    /// DXYN
+    /// The collision callback is only called for draws, that caused a collision.
+    fn test_draw_on_collision() {
+        let collisions = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let ccollisions = collisions.clone();
+        let mut chipset = get_default_chip();
+        chipset.on_collision(move || {
+            ccollisions.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+        let chip = chipset.chipset_mut();
+        let pc = chip.program_counter;
+
+        // draw the first row of the `0` font (0xF0) three times at (0, 0)
+        chip.index_register = display::fontset::LOCATION;
+        write_opcode_to_memory(chip, pc, 0xD011);
+        write_opcode_to_memory(chip, pc + 2, 0xD011);
+        write_opcode_to_memory(chip, pc + 4, 0xD011);
+
+        assert_eq!(chip.next(), Ok(Operation::Draw));
+        assert_eq!(0, collisions.load(std::sync::atomic::Ordering::SeqCst));
+        assert_eq!(chip.next(), Ok(Operation::Draw));
+        assert_eq!(1, collisions.load(std::sync::atomic::Ordering::SeqCst));
+        assert_eq!(chip.next(), Ok(Operation::Draw));
+        assert_eq!(1, collisions.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    /// DXYN
+    /// The toggled pixels of the last draw are recorded, if enabled.
+    fn test_draw_toggled_pixels() {
+        assert_eq!(None, get_default_chip().toggled_pixels());
+
+        let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+            .with_draw_deltas(true)
+            .build();
+        let chip = chipset.chipset_mut();
+        let pc = chip.program_counter;
+
+        // draw the first row of the `0` font (0xF0) at (2, 1)
+        chip.index_register = display::fontset::LOCATION;
+        chip.registers[0] = 2;
+        chip.registers[1] = 1;
+        chip.display[1][3] = true;
+        write_opcode_to_memory(chip, pc, 0xD011);
+
+        assert_eq!(chip.next(), Ok(Operation::Draw));
+        assert_eq!(
+            Some(&[(2, 1), (3, 1), (4, 1), (5, 1)][..]),
+            chipset.toggled_pixels()
+        );
+        assert!(!chipset.get_display()[1][3]);
+
+        chipset.reset(false);
+        assert_eq!(Some(&[][..]), chipset.toggled_pixels());
+    }
+
+    #[test]
+    /// DXYN
+    /// With the slow draw every step draws a single row of the sprite.
+    fn test_draw_slow() {
+        let mut chipset: ChipSet<Worker, NoCallback> =
+            ChipSetBuilder::new(get_base()).with_slow_draw(true).build();
+        let chip = chipset.chipset_mut();
+        let pc = chip.program_counter;
+
+        // draw the first three rows of the `0` font (0xF0, 0x90, 0x90) at (0, 30), the
+        // last row is clipped, while the second row collides
+        chip.index_register = display::fontset::LOCATION;
+        chip.registers[1] = 30;
+        chip.display[31][0] = true;
+        write_opcode_to_memory(chip, pc, 0xD013);
+
+        assert_eq!(chip.next(), Ok(Operation::Draw));
+        assert_eq!(pc, chip.program_counter);
+        assert!(chip.display[30][..4].iter().all(|&pixel| pixel));
+        assert!(chip.display[31][0]);
+        assert_eq!(0, chip.registers[cpu::register::LAST]);
+
+        assert_eq!(chip.next(), Ok(Operation::Draw));
+        assert_eq!(pc + 2, chip.program_counter);
+        assert!(!chip.display[31][0]);
+        assert!(chip.display[31][3]);
+        assert_eq!(1, chip.registers[cpu::register::LAST]);
+        assert!(chip.pending_draw.is_none());
+    }
+
+    #[test]
+    /// DXYN
+    /// On a filled display every drawn pixel is flipped off and sets VF.
+    fn test_draw_on_filled_display() {
+        let mut chipset = get_default_chip();
+        chipset.fill_display(true);
+        let chip = chipset.chipset_mut();
+        let (reg_x, reg_y) = (0x1, 0x2);
+        let opcode = 0xD << (3 * 4) ^ (reg_x as u16) << (2 * 4) ^ (reg_y as u16) << 4 ^ 0x5;
+
+        chip.registers[reg_x] = 0;
+        chip.registers[reg_y] = 0;
+        chip.index_register = display::fontset::LOCATION;
+
+        let pc = chip.program_counter;
+        write_opcode_to_memory(chip, pc, opcode);
+
+        assert_eq!(Ok(Operation::Draw), chip.next());
+        assert_eq!(1, chip.registers[cpu::register::LAST]);
+
+        for (y, row) in display::fontset::FONTSET[..5].iter().enumerate() {
+            for x in 0..8 {
+                let drawn = (row >> (7 - x)) & 1 == 1;
+                assert_eq!(!drawn, chip.display[y][x]);
+            }
+        }
+    }
+}
 
 mod e {
     use crate::OpcodeError;
@@ -960,6 +2878,79 @@ mod e {
         }
     }
 
+    #[test]
+    fn test_skip_key_pressed_masked() {
+        let rom = get_base();
+        let reg = 0x1;
+
+        let keyboard = [true; keyboard::SIZE];
+
+        let mut chipset = setup_chip(rom);
+        chipset.set_key_mask(!(1 << reg));
+        let chip = chipset.chipset_mut();
+        chip.set_keyboard(&keyboard);
+
+        chip.registers[reg] = reg as u8;
+        let opcode = 0xE << (3 * 4) ^ (reg as Opcode) << (2 * 4) ^ 0x9E;
+        let pc = chip.program_counter;
+
+        write_opcode_to_memory(chip, pc, opcode);
+
+        assert_eq!(chip.next(), Ok(Operation::None));
+
+        // the masked key reads as not pressed, so there is no skip
+        assert_eq!(chip.program_counter, pc + memory::opcodes::SIZE);
+    }
+
+    #[test]
+    fn test_skip_key_out_of_range() {
+        let reg = 0x1;
+        let mut chipset = setup_chip(get_base());
+        let chip = chipset.chipset_mut();
+        chip.set_keyboard(&[true; keyboard::SIZE]);
+        // a key past the keyboard is never pressed
+        chip.registers[reg] = 0x20;
+
+        for (suffix, skip) in [(0x9E, 0), (0xA1, 1)] {
+            let opcode = 0xE << (3 * 4) ^ (reg as Opcode) << (2 * 4) ^ suffix;
+            let pc = chip.program_counter;
+            write_opcode_to_memory(chip, pc, opcode);
+
+            assert_eq!(chip.next(), Ok(Operation::None));
+            assert_eq!(
+                chip.program_counter,
+                pc + (skip + 1) * memory::opcodes::SIZE
+            );
+        }
+    }
+
+    #[test]
+    fn test_skip_key_pressed_debounce() {
+        let reg = 0x1;
+        let pc = cpu::PROGRAM_COUNTER;
+        let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+            .with_key_debounce_cycles(4)
+            .build();
+
+        // poll the key, on a skip jump back, otherwise jump back as well
+        chipset.write_opcode_at(pc, 0xE << (3 * 4) ^ (reg as Opcode) << (2 * 4) ^ 0x9E);
+        chipset.write_opcode_at(pc + 2, 0x1000 ^ pc as Opcode);
+        chipset.write_opcode_at(pc + 4, 0x1000 ^ pc as Opcode);
+
+        let chip = chipset.chipset_mut();
+        chip.set_keyboard(&[true; keyboard::SIZE]);
+        chip.registers[reg] = reg as u8;
+
+        // the key is held, but only read as pressed once every four cycles
+        for pressed in [true, false, true] {
+            assert_eq!(chip.next(), Ok(Operation::None));
+            let next = if pressed { pc + 4 } else { pc + 2 };
+            assert_eq!(chip.program_counter, next);
+            assert_eq!(chip.next(), Ok(Operation::None));
+            assert_eq!(chip.program_counter, pc);
+        }
+    }
+
     #[test]
     fn test_wrong_opcode() {
         let rom = get_base();
@@ -1013,9 +3004,9 @@ mod f {
 
         assert_ne!(chip.registers[reg], dt);
 
-        // wait 1 s to make sure that the counter reaches 0
+        // the counter takes 1 s to reach 0, so wait a little longer to make sure
         chip.delay_timer.set_value(dt);
-        std::thread::sleep(Duration::from_secs(1));
+        std::thread::sleep(Duration::from_millis(1100));
 
         assert_eq!(Ok(Operation::None), chip.next());
 
@@ -1061,6 +3052,65 @@ mod f {
         assert_eq!(chip.registers[reg] as usize, key);
     }
 
+    #[test]
+    // FX0A
+    // Of multiple keys pressed at once the lowest index is stored.
+    fn test_await_key_press_multiple() {
+        let mut chipset = get_default_chip();
+        let chip = chipset.chipset_mut();
+        let reg = 0xA;
+        let opcode = 0xF << (3 * 4) ^ (reg as u16) << (2 * 4) ^ 0x0A;
+
+        let pc = chip.program_counter;
+        write_opcode_to_memory(chip, pc, opcode);
+        write_opcode_to_memory(chip, pc + memory::opcodes::SIZE, opcode);
+
+        // a key held before the wait started is not a new press
+        chip.set_key(1, true);
+        assert_eq!(Ok(Operation::Wait), chip.next());
+
+        // pressed within the same host frame, with the highest key last
+        for key in [2, 5, 9] {
+            chip.set_key(key, true);
+        }
+        assert_eq!(9, chip.get_keyboard_read().get_last().unwrap().get_index());
+
+        assert_eq!(Ok(Operation::Wait), chip.next());
+        assert_eq!(chip.program_counter, pc + memory::opcodes::SIZE);
+        assert_eq!(2, chip.registers[reg]);
+    }
+
+    #[test]
+    // FX0A
+    // A masked key press is ignored and the opcode keeps on waiting.
+    fn test_await_key_press_masked() {
+        let mut chipset = get_default_chip();
+        let masked = 4;
+        chipset.set_key_mask(!(1 << masked));
+        let chip = chipset.chipset_mut();
+        let key = 5;
+        let reg = 0xA;
+        let opcode = 0xF << (3 * 4) ^ (reg as u16) << (2 * 4) ^ 0x0A;
+
+        let pc = chip.program_counter;
+
+        write_opcode_to_memory(chip, pc, opcode);
+        write_opcode_to_memory(chip, pc + memory::opcodes::SIZE, opcode);
+
+        assert_eq!(Ok(Operation::Wait), chip.next());
+
+        chip.set_key(masked, true);
+        assert_eq!(Ok(Operation::Wait), chip.next());
+        assert_eq!(chip.program_counter, pc);
+
+        chip.registers[reg] = 0;
+        chip.set_key(key, true);
+        assert_eq!(Ok(Operation::Wait), chip.next());
+
+        assert_eq!(chip.program_counter, pc + memory::opcodes::SIZE);
+        assert_eq!(chip.registers[reg] as usize, key);
+    }
+
     #[test]
     /// FX15
     /// Sets the delay timer to VX.   
@@ -1197,6 +3247,50 @@ mod f {
         test(0, 0, 0, 0, 0);
     }
 
+    /// FX33, FX55 and FX65
+    /// The memory past its end is accessed according to the memory bounds quirk.
+    #[test]
+    fn test_memory_bounds() {
+        use crate::quirks::MemoryBounds::{Clamp, Error, Wrap};
+
+        let end = memory::SIZE;
+        let error = Err(ProcessError::MemoryOutOfBounds(end));
+        let untouched = [0xAA, 0xBB, 0xCC, 0xDD];
+        // (opcode, bounds, result, first and last two bytes, V0 to V2 after)
+        let cases = [
+            // FX33 with the digits 2, 4 and 6
+            (0xF033, Error, error, untouched, [246, 1, 2]),
+            (0xF033, Wrap, Ok(()), [6, 0xBB, 2, 4], [246, 1, 2]),
+            (0xF033, Clamp, Ok(()), [0xAA, 0xBB, 2, 6], [246, 1, 2]),
+            // FX55 storing V0 to V2
+            (0xF255, Error, error, untouched, [246, 1, 2]),
+            (0xF255, Wrap, Ok(()), [2, 0xBB, 246, 1], [246, 1, 2]),
+            (0xF255, Clamp, Ok(()), [0xAA, 0xBB, 246, 2], [246, 1, 2]),
+            // FX65 loading V0 to V2
+            (0xF265, Error, error, untouched, [246, 1, 2]),
+            (0xF265, Wrap, Ok(()), untouched, [0xCC, 0xDD, 0xAA]),
+            (0xF265, Clamp, Ok(()), untouched, [0xCC, 0xDD, 0xDD]),
+        ];
+
+        for (opcode, bounds, result, bytes, registers) in cases {
+            let mut chipset = get_default_chip();
+            let chip = chipset.chipset_mut();
+            let pc = chip.program_counter;
+            chip.quirks.memory_bounds = bounds;
+            chip.index_register = end - 2;
+            chip.registers[..3].copy_from_slice(&[246, 1, 2]);
+            chip.memory[..2].copy_from_slice(&[0xAA, 0xBB]);
+            chip.memory[(end - 2)..].copy_from_slice(&[0xCC, 0xDD]);
+            write_opcode_to_memory(chip, pc, opcode);
+
+            let context = format!("{:#06X} with {:?}", opcode, bounds);
+            assert_eq!(result, chip.next().map(|_| ()), "{}", context);
+            assert_eq!(bytes[..2], chip.memory[..2], "{}", context);
+            assert_eq!(bytes[2..], chip.memory[(end - 2)..], "{}", context);
+            assert_eq!(registers, chip.registers[..3], "{}", context);
+        }
+    }
+
     /// FX55
     /// Stores V0 to VX (including VX) in memory starting at address I. The offset from I
     /// is increased by 1 for each value written, but I itself is left unmodified.
@@ -1262,4 +3356,53 @@ mod f {
 
         assert_eq!(chip.program_counter, pc);
     }
+
+    #[test]
+    /// FX75 / FX85
+    /// The RPL user flags are round tripped through the registers in the SCHIP mode.
+    fn test_rpl_flags() {
+        let mut chipset: ChipSet<Worker, NoCallback> = ChipSetBuilder::new(get_base())
+            .with_mode(opcode::Mode::SChip)
+            .build();
+        let pc = cpu::PROGRAM_COUNTER;
+        // LD R, V3; LD V3, R
+        chipset.write_opcode_at(pc, 0xF375);
+        chipset.write_opcode_at(pc + 2, 0xF385);
+        let registers = [1, 2, 3, 4, 5, 6, 7, 8];
+        chipset.registers_mut()[..8].copy_from_slice(&registers);
+
+        assert_eq!(Ok(Operation::None), chipset.step());
+        assert_eq!([1, 2, 3, 4, 0, 0, 0, 0], chipset.get_rpl_flags());
+
+        chipset.registers_mut().fill(0);
+        assert_eq!(Ok(Operation::None), chipset.step());
+        assert_eq!(&[1, 2, 3, 4, 0], &chipset.chipset().registers[..5]);
+
+        // the flags survive a reset and can be set by the host
+        chipset.reset(false);
+        assert_eq!([1, 2, 3, 4, 0, 0, 0, 0], chipset.get_rpl_flags());
+        chipset.set_rpl_flags([9; cpu::RPL_FLAGS]);
+        chipset.write_opcode_at(pc, 0xF785);
+        assert_eq!(Ok(Operation::None), chipset.step());
+        assert_eq!(&[9; 8], &chipset.chipset().registers[..8]);
+
+        // there are only 8 flags
+        chipset.write_opcode_at(pc + 2, 0xF875);
+        assert_eq!(
+            Err(OpcodeError::InvalidOpcode(0xF875).into()),
+            chipset.step()
+        );
+    }
+
+    #[test]
+    /// FX75
+    /// The RPL user flags are not supported in the Chip8 mode.
+    fn test_rpl_flags_chip8() {
+        let mut chipset = get_default_chip().with_opcode_at(cpu::PROGRAM_COUNTER, 0xF075);
+        assert_eq!(
+            Err(OpcodeError::InvalidOpcode(0xF075).into()),
+            chipset.step()
+        );
+        assert_eq!([0; cpu::RPL_FLAGS], chipset.get_rpl_flags());
+    }
 }
@@ -0,0 +1,62 @@
+//! The log of the memory writes of the [`chipset`](super::ChipSet), which is only
+//! available with the `trace` feature.
+//! This implementation was split up into this file for smaller file sizes and higher
+//! cohesion.
+
+use super::{ChipSet, InternalChipSet};
+use crate::{
+    opcode::{self, Opcode},
+    timer::{TimedWorker, TimerCallback},
+};
+
+/// A single byte written to the memory by an opcode.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MemWrite {
+    /// The program counter of the opcode, that wrote the byte.
+    pub pc: usize,
+    /// The raw opcode, that wrote the byte.
+    pub opcode: Opcode,
+    /// The written memory address.
+    pub addr: usize,
+    /// The written value.
+    pub value: u8,
+}
+
+impl InternalChipSet {
+    /// Will log the bytes, that the opcode at the program counter is about to write to
+    /// the given addresses. This has to be called before the write, as an opcode might
+    /// overwrite itself.
+    pub(super) fn trace_writes(&mut self, addresses: impl Iterator<Item = usize>, data: &[u8]) {
+        let pc = self.program_counter;
+        let opcode = opcode::build_opcode(&self.memory, pc).unwrap_or_default();
+        for (addr, &value) in addresses.zip(data) {
+            log::trace!(
+                "{:#06X}: {:#06X} wrote {:#04X} to {:#06X}",
+                pc,
+                opcode,
+                value,
+                addr
+            );
+            self.write_log.push(MemWrite {
+                pc,
+                opcode,
+                addr,
+                value,
+            });
+        }
+    }
+}
+
+impl<W, S> ChipSet<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    /// Will return the memory writes of `FX33` and `FX55` since the last call, the
+    /// oldest first, so that the opcode clobbering a value (e.g. corrupting the save
+    /// data) can be found. Every write is logged with the `trace` level as well. The log
+    /// grows until it is taken and is cleared by a reset.
+    pub fn take_write_log(&mut self) -> Vec<MemWrite> {
+        std::mem::take(&mut self.chipset.write_log)
+    }
+}
@@ -0,0 +1,50 @@
+//! The sizes and dimensions of the chip, collected in one place for downstream code.
+//!
+//! The values are taken from the [`definitions`](crate::definitions), whose layout
+//! follows the internal organization and might change. The names used here are the
+//! stable ones.
+//!
+//! # Example
+//! ```rust
+//! # use chip::{chip8::ChipSet, constants, resources::RomArchives, timer::{NoCallback, Worker}};
+//! let chip: ChipSet<Worker, NoCallback> = RomArchives::new().load("PONG").unwrap();
+//! let frame = chip.frame();
+//! assert_eq!(constants::DISPLAY_WIDTH, frame.width());
+//! assert_eq!(constants::DISPLAY_HEIGHT, frame.height());
+//! ```
+
+use crate::definitions::{cpu, display, keyboard, memory, timer};
+
+/// The amount of pixels in a row of the display.
+pub const DISPLAY_WIDTH: usize = display::HEIGHT;
+/// The amount of rows of the display.
+pub const DISPLAY_HEIGHT: usize = display::WIDTH;
+/// The amount of pixels the display has.
+pub const DISPLAY_RESOLUTION: usize = display::RESOLUTION;
+
+/// The size of the memory in bytes.
+pub const MEMORY_SIZE: usize = memory::SIZE;
+/// The size of a single opcode in bytes.
+pub const OPCODE_SIZE: usize = memory::opcodes::SIZE;
+/// The address, at which the rom is loaded and the execution starts.
+pub const PROGRAM_COUNTER: usize = cpu::PROGRAM_COUNTER;
+/// The largest rom, that fits into the memory.
+pub const MAX_ROM_SIZE: usize = MEMORY_SIZE - PROGRAM_COUNTER;
+
+/// The amount of data registers (`V0` to `VF`).
+pub const REGISTER_SIZE: usize = cpu::register::SIZE;
+/// The maximum amount of nested subroutine calls.
+pub const STACK_SIZE: usize = cpu::stack::SIZE;
+
+/// The amount of keys of the keyboard.
+pub const KEYBOARD_SIZE: usize = keyboard::SIZE;
+
+/// The address of the built in font in memory.
+pub const FONTSET_LOCATION: usize = display::fontset::LOCATION;
+/// The amount of bytes a single character of the font occupies.
+pub const FONT_CHARACTER_SIZE: usize = display::fontset::FONTSET.len() / 16;
+
+/// The frequency in hertz at which the delay and sound timer count down.
+pub const TIMER_HZ: u8 = timer::HERZ;
+/// The frequency in hertz at which the cpu runs by default.
+pub const CPU_HZ: u64 = cpu::HERTZ;
@@ -17,12 +17,15 @@ pub mod memory {
 /// The definitions for the cpu
 pub mod cpu {
     /// The starting point for the program
-    pub(in super::super) const PROGRAM_COUNTER: usize = 0x0200;
+    pub const PROGRAM_COUNTER: usize = 0x0200;
     /// The amound of hertz the emulation shall run at.
     pub const HERTZ: u64 = 500;
     /// The amount of times the cpu shall run per second
     pub const INTERVAL: u64 = 1000 / HERTZ;
 
+    /// The amount of RPL user flags of the SuperChip.
+    pub const RPL_FLAGS: usize = 8;
+
     /// The definitions needed for the register
     pub(crate) mod register {
         /// The size of the chip set registers
@@ -52,6 +55,10 @@ pub mod sound {
 
     /// How long the sound shall be played for.
     pub const DURRATION: Duration = Duration::from_millis(250);
+    /// The frequency of the generated tone in hertz.
+    pub const FREQUENCY: f32 = 440.0;
+    /// The amplitude of the generated tone.
+    pub const AMPLITUDE: f32 = 0.25;
 }
 
 /// The display definitions
@@ -1,6 +1,6 @@
 //! Abstractions over the keyboard and display.
 
-use crate::definitions::keyboard;
+use crate::definitions::{display, keyboard};
 use parking_lot::RwLock;
 use std::sync::Arc;
 
@@ -8,6 +8,12 @@ use std::sync::Arc;
 pub trait DisplayCommands {
     /// Will display all from the pixels
     fn display<M: AsRef<[V]>, V: AsRef<[bool]>>(&mut self, pixels: M);
+
+    /// Will return the `(width, height)` of the output surface in pixels, by default
+    /// this is the logical display size of `64x32`.
+    fn dimensions(&self) -> (usize, usize) {
+        (display::HEIGHT, display::WIDTH)
+    }
 }
 
 /// The trait responsible for writing the keyboard data
@@ -67,7 +73,11 @@ pub struct Keyboard {
     /// One skips an instruction if a specific key is pressed, while another does the same if a
     /// specific key is not pressed. The third waits for a key press, and then stores it in one of
     /// the data registers.
-    keys: [bool; keyboard::SIZE],
+    ///
+    /// The keys are stored as bitmask, where bit `n` is key `n`.
+    keys: u16,
+    /// The same keys as single values, so that they can still be borrowed as slice.
+    pressed: [bool; keyboard::SIZE],
     last: Option<Key>,
 }
 
@@ -86,25 +96,53 @@ impl Keyboard {
         );
 
         // check if the key state has changed or not
-        if self.keys[key] == to {
+        let current = self.is_pressed(key);
+        if current == to {
             return;
         }
         // setup last
-        self.last = Some(Key::new(key, self.keys[key], to));
+        self.last = Some(Key::new(key, current, to));
 
         // write back solution
-        self.keys[key] = to;
+        self.keys ^= 1 << key;
+        self.pressed[key] = to;
     }
 
     /// Will set multiple keys
     pub fn set_mult(&mut self, keys: &[bool; keyboard::SIZE]) {
-        self.keys.copy_from_slice(keys);
+        let bitmask = keys.iter().enumerate().fold(0, |bitmask, (key, &pressed)| {
+            bitmask | (pressed as u16) << key
+        });
+        self.set_bitmask(bitmask);
+    }
+
+    /// Will set all the keys at once, where bit `n` of the bitmask is key `n`.
+    pub fn set_bitmask(&mut self, bitmask: u16) {
+        self.keys = bitmask;
+        for (key, pressed) in self.pressed.iter_mut().enumerate() {
+            *pressed = bitmask & (1 << key) != 0;
+        }
         self.last = None;
     }
 
+    /// Will get all the keys as bitmask, where bit `n` is key `n`.
+    pub fn bitmask(&self) -> u16 {
+        self.keys
+    }
+
     /// Will get all the keys
     pub fn get_keys(&self) -> &[bool] {
-        &self.keys
+        &self.pressed
+    }
+
+    /// Checks if the given key is pressed, a key past the keyboard is never pressed.
+    pub fn is_pressed(&self, key: usize) -> bool {
+        key < keyboard::SIZE && self.keys & (1 << key) != 0
+    }
+
+    /// Checks if any key is pressed.
+    pub fn any_pressed(&self) -> bool {
+        self.keys != 0
     }
 
     /// Will get the last changes key
@@ -113,6 +151,6 @@ impl Keyboard {
     }
 
     pub fn get_keyboard(&self) -> &[bool] {
-        &self.keys
+        self.get_keys()
     }
 }
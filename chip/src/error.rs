@@ -6,10 +6,26 @@ use crate::opcode::Opcode;
 pub enum ProcessError {
     #[error("Invalid opcode state '{0}'.")]
     Opcode(#[from] OpcodeError),
+    #[error("Unable to fetch the next opcode '{0}'.")]
+    OpcodeFetch(OpcodeError),
     #[error("Invalid stack state '{0}'.")]
     Stack(#[from] StackError),
+    #[error("The memory address {0:#06X} is out of bounds.")]
+    MemoryOutOfBounds(usize),
+    #[error("The program counter {0:#06X} is not aligned to an opcode.")]
+    MisalignedProgramCounter(usize),
     #[error("There is no valid chipset initialized.")]
     UninitializedChipset,
+    #[error("The instruction budget has been exhausted.")]
+    BudgetExceeded,
+    #[error("The pixel ({x}, {y}) is outside of the display.")]
+    PixelOutOfBounds { x: usize, y: usize },
+    #[error("The worker of a timer stopped, so that the timers no longer count down.")]
+    TimerFailure,
+    #[error("The display data has {size} bytes instead of {expected}.")]
+    DisplaySize { size: usize, expected: usize },
+    #[error("The register V{0:X} is outside of the register bank.")]
+    InvalidRegister(usize),
 }
 
 #[derive(Error, Debug, PartialEq, Clone, Copy)]
@@ -31,3 +47,62 @@ pub enum StackError {
     #[error("Unexpected error during stack execution")]
     Unexpected,
 }
+
+#[derive(Error, Debug)]
+pub enum ChipError {
+    #[error("Unable to extract the rom '{0}'.")]
+    Archive(#[from] zip::result::ZipError),
+    #[error("The rom has a size of {size} bytes, but at most {max} bytes fit into the memory.")]
+    RomSize { size: usize, max: usize },
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SnapshotError {
+    #[error("The memory of the snapshot has an invalid size of {0}.")]
+    MemorySize(usize),
+    #[error("The stack of the snapshot has an invalid size of {0}.")]
+    StackSize(usize),
+    #[error("The registers of the snapshot have an invalid size of {0}.")]
+    RegisterSize(usize),
+    #[error("The program counter {0:#X} of the snapshot is out of memory.")]
+    ProgramCounter(usize),
+    #[error(
+        "The rom of the snapshot has a size of {0} bytes, which does not fit into the memory."
+    )]
+    RomSize(usize),
+    #[error("The index register {0:#X} of the snapshot is out of memory.")]
+    IndexRegister(usize),
+    #[error("The return address {0:#X} on the stack of the snapshot is out of memory.")]
+    StackAddress(usize),
+    #[error("The display of the snapshot has an invalid size.")]
+    DisplaySize,
+    #[error("The compressed snapshot is malformed.")]
+    Malformed,
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum AsmError {
+    #[error("Line {line}: unknown instruction '{name}'.")]
+    UnknownInstruction { line: usize, name: String },
+    #[error("Line {line}: invalid operands '{operands}'.")]
+    InvalidOperands { line: usize, operands: String },
+    #[error("Line {line}: the value {value:#X} does not fit into the operand.")]
+    ValueOutOfRange { line: usize, value: usize },
+    #[error("Line {line}: unknown label '{label}'.")]
+    UnknownLabel { line: usize, label: String },
+    #[error("Line {line}: the label '{label}' was already defined.")]
+    DuplicateLabel { line: usize, label: String },
+}
+
+impl AsmError {
+    /// The line (starting at `1`) of the source, that caused the error.
+    pub fn line(&self) -> usize {
+        match self {
+            AsmError::UnknownInstruction { line, .. }
+            | AsmError::InvalidOperands { line, .. }
+            | AsmError::ValueOutOfRange { line, .. }
+            | AsmError::UnknownLabel { line, .. }
+            | AsmError::DuplicateLabel { line, .. } => *line,
+        }
+    }
+}
@@ -1,8 +1,11 @@
+pub mod asm;
 pub mod chip8;
+pub mod constants;
 pub mod definitions;
 pub mod devices;
 mod error;
 pub mod opcode;
+pub mod quirks;
 pub mod resources;
 pub mod timer;
 
@@ -1,5 +1,8 @@
 //! Opcode abstractions, functionality and constants.
-use std::convert::{TryFrom, TryInto};
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt,
+};
 
 use crate::{
     definitions::{cpu, memory},
@@ -38,7 +41,7 @@ pub type Opcode = u16;
 ///
 /// - `data` - A slice of u8 data entries used to generate the opcodes
 /// - `pointer` - Where in the data the opcode shall be extracted, so `pointer` and `pointer + 1` make
-/// the opcode up
+///   the opcode up
 ///
 /// # Example
 /// ```rust
@@ -127,9 +130,6 @@ impl OpcodeTrait for Opcode {
         (self & OPCODE_MASK_F000) as usize
     }
 
-    /// this is an opcode extractor for the opcode type `TNNN`
-    /// - `T` is the opcode type
-    /// - `NNN` is a register index
     /// this is an opcode extractor for the opcode type `TNNN`
     /// - `T` is the opcode type
     /// - `NNN` is a register index
@@ -396,15 +396,40 @@ pub enum Zero {
     Clear,
     /// Returns from the subroutine
     Return,
+    /// Exits the interpreter (SuperChip only)
+    Exit,
+    /// Scrolls the display down by the given amount of pixels (SuperChip only)
+    ScrollDown(usize),
+    /// Scrolls the display right by 4 pixels (SuperChip only)
+    ScrollRight,
+    /// Scrolls the display left by 4 pixels (SuperChip only)
+    ScrollLeft,
 }
 
-implTryIntoEnum!(Zero : Opcode :
-    // 00E0
-    // clear display
-    0x00E0 => Zero::Clear,
-    // 00EE
-    // Return from sub routine => pop from stack
-    0x00EE => Zero::Return,
+implTryIntoInner!(Zero : Opcode :
+    |value: Opcode| {
+        match value {
+            // 00CN
+            // Scroll the display down by N pixels
+            0x00C0..=0x00CF => Ok(Zero::ScrollDown((value & 0xF) as usize)),
+            // 00E0
+            // clear display
+            0x00E0 => Ok(Zero::Clear),
+            // 00EE
+            // Return from sub routine => pop from stack
+            0x00EE => Ok(Zero::Return),
+            // 00FB
+            // Scroll the display right by 4 pixels
+            0x00FB => Ok(Zero::ScrollRight),
+            // 00FC
+            // Scroll the display left by 4 pixels
+            0x00FC => Ok(Zero::ScrollLeft),
+            // 00FD
+            // Exit the interpreter
+            0x00FD => Ok(Zero::Exit),
+            _ => Err(()),
+        }
+    }
 );
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -591,6 +616,8 @@ pub enum FifteenOpcode {
     StoreBCD,
     StoreV0ToVx,
     FillV0ToVx,
+    StoreFlags,
+    LoadFlags,
 }
 
 implTryIntoEnum!(FifteenOpcode : u8 :
@@ -633,6 +660,12 @@ implTryIntoEnum!(FifteenOpcode : u8 :
     // offset from I is increased by 1 for each value written, but I itself is left
     // unmodified.
     0x65 => FifteenOpcode::FillV0ToVx,
+    // FX75 (SCHIP)
+    // Stores V0 to VX (including VX, X < 8) in the RPL user flags.
+    0x75 => FifteenOpcode::StoreFlags,
+    // FX85 (SCHIP)
+    // Fills V0 to VX (including VX, X < 8) with the values of the RPL user flags.
+    0x85 => FifteenOpcode::LoadFlags,
 );
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -694,6 +727,232 @@ impl TryFrom<Opcode> for Opcodes {
     }
 }
 
+impl Opcodes {
+    /// Will return the highest data register addressed by the `X` and `Y` operands, if
+    /// the opcode has any. Registers used implicitly (e.g. `V0` by `BNNN` or the flag
+    /// register `VF`) are not included.
+    pub fn highest_register(&self) -> Option<usize> {
+        match *self {
+            Opcodes::Zero(_)
+            | Opcodes::One(_)
+            | Opcodes::Two(_)
+            | Opcodes::A(_)
+            | Opcodes::B(_) => None,
+            Opcodes::Three(Three { x, .. })
+            | Opcodes::Four(Four { x, .. })
+            | Opcodes::Six(Six { x, .. })
+            | Opcodes::Seven(Seven { x, .. })
+            | Opcodes::C(Twelve { x, .. })
+            | Opcodes::E(Fourteen { x, .. })
+            | Opcodes::F(Fifteen { x, .. }) => Some(x),
+            Opcodes::Five(Five { x, y })
+            | Opcodes::Eight(Eight { x, y, .. })
+            | Opcodes::Nine(Nine { x, y })
+            | Opcodes::D(Thirteen { x, y, .. }) => Some(x.max(y)),
+        }
+    }
+}
+
+/// The instruction sets supported by the interpreter.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Mode {
+    /// The original Chip8 instruction set.
+    #[default]
+    Chip8,
+    /// The SuperChip extensions of the Chip8 instruction set, so far only the scrolling
+    /// (`00CN`, `00FB` and `00FC`) in the low resolution, the exit opcode (`00FD`) and the
+    /// RPL user flags (`FX75` and `FX85`) are supported.
+    SChip,
+}
+
+impl Mode {
+    /// Checks if the decoded opcode can be executed in this mode.
+    pub fn supports(&self, opcode: &Opcodes) -> bool {
+        match opcode {
+            Opcodes::Zero(
+                Zero::Exit | Zero::ScrollDown(_) | Zero::ScrollRight | Zero::ScrollLeft,
+            ) => *self == Mode::SChip,
+            Opcodes::F(Fifteen {
+                ops: FifteenOpcode::StoreFlags | FifteenOpcode::LoadFlags,
+                x,
+            }) => *self == Mode::SChip && *x < cpu::RPL_FLAGS,
+            _ => true,
+        }
+    }
+}
+
+/// Describes a single opcode supported by the interpreter.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct OpcodeInfo {
+    /// The pattern of the opcode, where `X` and `Y` are register indices and `N` are
+    /// constants, e.g. `DXYN`.
+    pub pattern: &'static str,
+    /// The assembly mnemonic of the opcode, e.g. `DRW VX, VY, N`.
+    pub mnemonic: &'static str,
+    /// A short description of what the opcode does.
+    pub description: &'static str,
+}
+
+impl OpcodeInfo {
+    const fn new(pattern: &'static str, mnemonic: &'static str, description: &'static str) -> Self {
+        Self {
+            pattern,
+            mnemonic,
+            description,
+        }
+    }
+
+    /// Checks if the raw opcode fits the pattern, the placeholders match any nibble.
+    pub fn matches(&self, value: Opcode) -> bool {
+        self.pattern
+            .chars()
+            .rev()
+            .enumerate()
+            .all(|(i, c)| match c.to_digit(16) {
+                Some(digit) => (value >> (4 * i)) & 0xF == digit as Opcode,
+                None => true,
+            })
+    }
+}
+
+/// The catalog of the opcodes decoded by [`Opcodes::try_from`](Opcodes::try_from) in the
+/// [`Chip8`](Mode::Chip8) mode.
+const CHIP8_OPCODES: [OpcodeInfo; 34] = [
+    OpcodeInfo::new("00E0", "CLS", "Clear the display"),
+    OpcodeInfo::new("00EE", "RET", "Return from subroutine"),
+    OpcodeInfo::new("1NNN", "JP NNN", "Jump to address NNN"),
+    OpcodeInfo::new("2NNN", "CALL NNN", "Call subroutine at NNN"),
+    OpcodeInfo::new("3XNN", "SE VX, NN", "Skip next instruction if VX == NN"),
+    OpcodeInfo::new("4XNN", "SNE VX, NN", "Skip next instruction if VX != NN"),
+    OpcodeInfo::new("5XY0", "SE VX, VY", "Skip next instruction if VX == VY"),
+    OpcodeInfo::new("6XNN", "LD VX, NN", "VX = NN"),
+    OpcodeInfo::new("7XNN", "ADD VX, NN", "VX = VX + NN"),
+    OpcodeInfo::new("8XY0", "LD VX, VY", "VX = VY"),
+    OpcodeInfo::new("8XY1", "OR VX, VY", "VX = VX OR VY"),
+    OpcodeInfo::new("8XY2", "AND VX, VY", "VX = VX AND VY"),
+    OpcodeInfo::new("8XY3", "XOR VX, VY", "VX = VX XOR VY"),
+    OpcodeInfo::new(
+        "8XY4",
+        "ADD VX, VY",
+        "VX = VX + VY; VF = 1 if overflow else 0",
+    ),
+    OpcodeInfo::new(
+        "8XY5",
+        "SUB VX, VY",
+        "VX = VX - VY; VF = 1 if not borrow else 0",
+    ),
+    OpcodeInfo::new("8XY6", "SHR VX", "VF = LSB(VX); VX = VX >> 1"),
+    OpcodeInfo::new(
+        "8XY7",
+        "SUBN VX, VY",
+        "VX = VY - VX; VF = 1 if not borrow else 0",
+    ),
+    OpcodeInfo::new("8XYE", "SHL VX", "VF = MSB(VX); VX = VX << 1"),
+    OpcodeInfo::new("9XY0", "SNE VX, VY", "Skip next instruction if VX != VY"),
+    OpcodeInfo::new("ANNN", "LD I, NNN", "I = NNN"),
+    OpcodeInfo::new("BNNN", "JP V0, NNN", "Jump to address NNN + V0"),
+    OpcodeInfo::new("CXNN", "RND VX, NN", "VX = RND() AND NN"),
+    OpcodeInfo::new(
+        "DXYN",
+        "DRW VX, VY, N",
+        "Draw 8xN sprite at I to VX, VY; VF = 1 if collision else 0",
+    ),
+    OpcodeInfo::new(
+        "EX9E",
+        "SKP VX",
+        "Skip next instruction if key(VX) is pressed",
+    ),
+    OpcodeInfo::new(
+        "EXA1",
+        "SKNP VX",
+        "Skip next instruction if key(VX) is not pressed",
+    ),
+    OpcodeInfo::new("FX07", "LD VX, DT", "VX = DT"),
+    OpcodeInfo::new(
+        "FX0A",
+        "LD VX, K",
+        "Wait for key press, store key pressed in VX",
+    ),
+    OpcodeInfo::new("FX15", "LD DT, VX", "DT = VX"),
+    OpcodeInfo::new("FX18", "LD ST, VX", "ST = VX"),
+    OpcodeInfo::new("FX1E", "ADD I, VX", "I = I + VX"),
+    OpcodeInfo::new(
+        "FX29",
+        "LD F, VX",
+        "I = address of 4x5 font character in VX",
+    ),
+    OpcodeInfo::new(
+        "FX33",
+        "LD B, VX",
+        "Store BCD representation of VX at I, I+1 and I+2",
+    ),
+    OpcodeInfo::new(
+        "FX55",
+        "LD [I], VX",
+        "Store V0..VX (inclusive) to memory starting at I",
+    ),
+    OpcodeInfo::new(
+        "FX65",
+        "LD VX, [I]",
+        "Load V0..VX (inclusive) from memory starting at I",
+    ),
+];
+
+/// The catalog of the opcodes, that are added by the [`SChip`](Mode::SChip) mode.
+const SCHIP_OPCODES: [OpcodeInfo; 6] = [
+    OpcodeInfo::new("00CN", "SCD N", "Scroll the display down by N pixels"),
+    OpcodeInfo::new("00FB", "SCR", "Scroll the display right by 4 pixels"),
+    OpcodeInfo::new("00FC", "SCL", "Scroll the display left by 4 pixels"),
+    OpcodeInfo::new("00FD", "EXIT", "Exit the interpreter"),
+    OpcodeInfo::new(
+        "FX75",
+        "LD R, VX",
+        "Store V0..VX (inclusive, X < 8) in the RPL user flags",
+    ),
+    OpcodeInfo::new(
+        "FX85",
+        "LD VX, R",
+        "Load V0..VX (inclusive, X < 8) from the RPL user flags",
+    ),
+];
+
+/// Will return the catalog of all the opcodes supported in the given mode.
+///
+/// # Example
+/// ```rust
+/// # use chip::opcode::*;
+/// let opcodes = supported_opcodes(Mode::Chip8);
+/// let draw = opcodes.iter().find(|info| info.pattern == "DXYN").unwrap();
+/// assert_eq!(draw.mnemonic, "DRW VX, VY, N");
+/// ```
+pub fn supported_opcodes(mode: Mode) -> Vec<OpcodeInfo> {
+    match mode {
+        Mode::Chip8 => CHIP8_OPCODES.to_vec(),
+        Mode::SChip => [&CHIP8_OPCODES[..], &SCHIP_OPCODES[..]].concat(),
+    }
+}
+
+/// Will look up the catalog entry of the raw opcode in the given mode, without
+/// allocating the whole catalog.
+///
+/// # Example
+/// ```rust
+/// # use chip::opcode::*;
+/// assert_eq!(Some("8XY6"), opcode_info(0x8126, Mode::Chip8).map(|info| info.pattern));
+/// assert_eq!(None, opcode_info(0xF175, Mode::Chip8));
+/// ```
+pub fn opcode_info(value: Opcode, mode: Mode) -> Option<OpcodeInfo> {
+    let extensions: &[OpcodeInfo] = match mode {
+        Mode::Chip8 => &[],
+        Mode::SChip => &SCHIP_OPCODES,
+    };
+    CHIP8_OPCODES
+        .iter()
+        .chain(extensions)
+        .find(|info| info.matches(value))
+        .copied()
+}
+
 /// Represents a step of the program counter
 /// this requires the enum ProgramCounterStep
 /// to work.
@@ -712,6 +971,30 @@ pub enum Operation {
     Wait,
     /// A redraw command with the individual parameters
     Draw,
+    /// The whole display was cleared, so that a full redraw is needed
+    Clear,
+    /// The program requested to exit the interpreter, so that no further
+    /// opcodes are executed
+    Exit,
+    /// The display was scrolled by the given amount of pixels (positive is right and
+    /// down), so that the host can animate the movement. Hosts that do not animate it
+    /// can treat it like a [`Draw`](Self::Draw).
+    Scroll { dx: i32, dy: i32 },
+}
+
+/// Renders the operation in a compact form for traces, e.g. `draw` or
+/// `scroll dx=4 dy=0`.
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::None => write!(f, "none"),
+            Operation::Wait => write!(f, "wait"),
+            Operation::Draw => write!(f, "draw"),
+            Operation::Clear => write!(f, "clear"),
+            Operation::Exit => write!(f, "exit"),
+            Operation::Scroll { dx, dy } => write!(f, "scroll dx={} dy={}", dx, dy),
+        }
+    }
 }
 
 /// Handles the preprocessing before opcode execution.
@@ -738,6 +1021,9 @@ pub trait ChipOpcodes: ProgramCounter + ChipOpcodePreProcessHandler {
         // preprocess
         self.preprocess();
 
+        #[cfg(feature = "instrument")]
+        self.before_execute(opcode);
+
         let mut operation = Operation::None;
         let step_op = |(step, op)| {
             operation = op;
@@ -764,14 +1050,30 @@ pub trait ChipOpcodes: ProgramCounter + ChipOpcodePreProcessHandler {
         }?;
 
         self.step(step);
+
+        #[cfg(feature = "instrument")]
+        self.after_execute(opcode, &operation);
+
         Ok(operation)
     }
 
+    /// Is called right before the opcode is executed.
+    #[cfg(feature = "instrument")]
+    fn before_execute(&mut self, _opcode: &Opcodes) {}
+
+    /// Is called after the opcode was executed successfully.
+    #[cfg(feature = "instrument")]
+    fn after_execute(&mut self, _opcode: &Opcodes, _operation: &Operation) {}
+
     /// A multiuse opcode base for type `0NNN`
     ///
     /// - `0NNN` - Call     -                       - Calls machine code routine ([RCA 1802](https://en.wikipedia.org/wiki/RCA_1802) for COSMAC VIP) at address `NNN`. Not necessary for most ROMs.
     /// - `00E0` - Display  - `disp_clear()`        - Clears the screen.
     /// - `00EE` - Flow     - `return;`             - Returns from a subroutine.
+    /// - `00CN` - Display  - `scroll_down(N)`      - Scrolls the display down by `N` pixels (SuperChip only).
+    /// - `00FB` - Display  - `scroll_right(4)`     - Scrolls the display right by `4` pixels (SuperChip only).
+    /// - `00FC` - Display  - `scroll_left(4)`      - Scrolls the display left by `4` pixels (SuperChip only).
+    /// - `00FD` - Flow     - `exit();`             - Exits the interpreter (SuperChip only).
     ///
     /// Returns any possible error
     fn zero(&mut self, opcode: &Zero) -> Result<(ProgramCounterStep, Operation), ProcessError>;
@@ -857,7 +1159,7 @@ pub trait ChipOpcodes: ProgramCounter + ChipOpcodePreProcessHandler {
     /// - `EXA1` - KeyOp    - `if(key()!=Vx)`       - Skips the next instruction if the key stored in `VX` isn't pressed. (Usually the next instruction is a jump to skip a code block)
     ///
     /// Returns any possible error
-    fn e(&self, opcode: &Fourteen) -> Result<ProgramCounterStep, ProcessError>;
+    fn e(&mut self, opcode: &Fourteen) -> Result<ProgramCounterStep, ProcessError>;
 
     /// A multiuse opcode base for type `FXTT` (T is a sub opcode)
     ///
@@ -902,6 +1204,10 @@ mod tests {
             // Zero
             (0x00E0, Ok(Opcodes::Zero(Zero::Clear))),
             (0x00EE, Ok(Opcodes::Zero(Zero::Return))),
+            (0x00FD, Ok(Opcodes::Zero(Zero::Exit))),
+            (0x00C3, Ok(Opcodes::Zero(Zero::ScrollDown(3)))),
+            (0x00FB, Ok(Opcodes::Zero(Zero::ScrollRight))),
+            (0x00FC, Ok(Opcodes::Zero(Zero::ScrollLeft))),
             (0x00E1, Err("")),
             // One
             (0x1919, Ok(Opcodes::One(One { nnn: 0x919 }))),
@@ -1082,6 +1388,20 @@ mod tests {
                     ops: FifteenOpcode::FillV0ToVx,
                 })),
             ),
+            (
+                0xF075,
+                Ok(Opcodes::F(Fifteen {
+                    x: 0x0,
+                    ops: FifteenOpcode::StoreFlags,
+                })),
+            ),
+            (
+                0xF085,
+                Ok(Opcodes::F(Fifteen {
+                    x: 0x0,
+                    ops: FifteenOpcode::LoadFlags,
+                })),
+            ),
             (0xF0AA, Err("")),
         ];
         for (value, res) in tests {
@@ -1089,4 +1409,48 @@ mod tests {
             assert_eq!(conv, res.map_err(|_| OpcodeError::InvalidOpcode(value)));
         }
     }
+    #[test]
+    fn test_supported_opcodes_decode() {
+        for mode in [Mode::Chip8, Mode::SChip] {
+            for info in supported_opcodes(mode) {
+                // replace the placeholders with a valid value
+                let pattern: String = info
+                    .pattern
+                    .chars()
+                    .map(|c| if c.is_ascii_hexdigit() { c } else { '1' })
+                    .collect();
+                let value = Opcode::from_str_radix(&pattern, 16).unwrap();
+                let conv: Result<Opcodes, _> = value.try_into();
+                assert!(conv.is_ok(), "{} is not supported", info.pattern);
+                assert!(mode.supports(&conv.unwrap()), "{}", info.pattern);
+            }
+        }
+    }
+
+    #[test]
+    fn test_operation_display() {
+        assert_eq!("none", Operation::None.to_string());
+        assert_eq!("wait", Operation::Wait.to_string());
+        assert_eq!("draw", Operation::Draw.to_string());
+        assert_eq!("clear", Operation::Clear.to_string());
+        assert_eq!("exit", Operation::Exit.to_string());
+        assert_eq!(
+            "scroll dx=-4 dy=0",
+            Operation::Scroll { dx: -4, dy: 0 }.to_string()
+        );
+    }
+
+    #[test]
+    fn test_mode_supports() {
+        let flags: Opcodes = 0xF775.try_into().unwrap();
+        assert!(!Mode::Chip8.supports(&flags));
+        assert!(Mode::SChip.supports(&flags));
+
+        // there are only 8 flags
+        let flags: Opcodes = 0xF885.try_into().unwrap();
+        assert!(!Mode::SChip.supports(&flags));
+
+        let clear: Opcodes = 0x00E0.try_into().unwrap();
+        assert!(Mode::Chip8.supports(&clear));
+    }
 }
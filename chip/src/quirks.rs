@@ -0,0 +1,128 @@
+//! The configuration of the behaviours that differ between the Chip8 interpreters.
+
+use std::convert::TryFrom;
+
+use crate::{
+    definitions::memory,
+    opcode::{self, Mode, Opcodes},
+    resources::Rom,
+};
+
+/// How the `0NNN` opcode, which calls a machine code routine of the original hardware,
+/// is handled. `00E0` and `00EE` are not affected.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum SysCall {
+    /// The opcode is reported as unsupported.
+    #[default]
+    Error,
+    /// The opcode is skipped.
+    Ignore,
+    /// The address `NNN` is passed to the callback registered with
+    /// [`on_sys_call`](crate::chip8::ChipSet::on_sys_call) and the opcode is skipped.
+    Callback,
+}
+
+/// How the `DXYN` opcode combines the pixels of a sprite with the display.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum DrawMode {
+    /// The set pixels of the sprite toggle the pixels of the display, turning off a pixel
+    /// sets `VF` to `1`. This is the standard Chip8 behaviour.
+    #[default]
+    Xor,
+    /// The set pixels of the sprite turn on the pixels of the display, so that sprites
+    /// accumulate. `VF` is always set to `0`.
+    Or,
+    /// The pixels of the sprite replace the pixels of the display, including the unset
+    /// ones within the `8` pixels of a row. `VF` is always set to `0`.
+    Overwrite,
+}
+
+/// How `FX33`, `FX55` and `FX65` handle the bytes past the end of the memory, when `I`
+/// points close to it.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum MemoryBounds {
+    /// The opcode fails with
+    /// [`MemoryOutOfBounds`](crate::ProcessError::MemoryOutOfBounds) without accessing
+    /// the memory.
+    #[default]
+    Error,
+    /// The addresses wrap around to the start of the memory.
+    Wrap,
+    /// The addresses are clamped to the last byte of the memory.
+    Clamp,
+}
+
+/// The behaviours of the different Chip8 interpreters, so that the roms written
+/// against them can be run and analysed correctly.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Quirks {
+    /// The instruction set used to decode the opcodes.
+    pub mode: Mode,
+    /// How the `0NNN` machine code calls are handled.
+    pub sys_call: SysCall,
+    /// How sprites are drawn by `DXYN`.
+    pub draw_mode: DrawMode,
+    /// How the memory past its end is accessed by `FX33`, `FX55` and `FX65`.
+    pub memory_bounds: MemoryBounds,
+    /// If an opcode at an odd address is reported as
+    /// [`MisalignedProgramCounter`](crate::ProcessError::MisalignedProgramCounter). By
+    /// default this is enabled.
+    ///
+    /// Disabling it allows the few self-modifying roms, that execute from odd offsets on
+    /// purpose, to run. The risk is, that a broken or untrusted rom, which jumped into
+    /// the middle of an opcode, continues to run garbage instead of stopping.
+    pub enforce_alignment: bool,
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self {
+            mode: Mode::default(),
+            sys_call: SysCall::default(),
+            draw_mode: DrawMode::default(),
+            memory_bounds: MemoryBounds::default(),
+            enforce_alignment: true,
+        }
+    }
+}
+
+impl Quirks {
+    /// Will guess the quirks the rom was written against, based on the opcodes it uses.
+    ///
+    /// This is a best-effort heuristic: the rom is swept linearly, so that data stored
+    /// within the program might be taken for opcodes. Currently the
+    /// [`SChip`](Mode::SChip) mode is chosen, once an opcode is found that only exists in
+    /// that instruction set, every other setting keeps its default.
+    pub fn detect(rom: &Rom) -> Self {
+        let data = rom.get_data();
+        let is_schip = (0..data.len())
+            .step_by(memory::opcodes::SIZE)
+            .filter_map(|pointer| opcode::build_opcode(data, pointer).ok())
+            .filter_map(|value| Opcodes::try_from(value).ok())
+            .any(|opcode| !Mode::Chip8.supports(&opcode) && Mode::SChip.supports(&opcode));
+
+        let mode = if is_schip { Mode::SChip } else { Mode::Chip8 };
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let rom = Rom::from_bytes("CHIP8", &[0x00, 0xE0, 0xF3, 0x65, 0x12, 0x00]);
+        assert_eq!(Quirks::default(), Quirks::detect(&rom));
+
+        let rom = Rom::from_bytes("SCHIP", &[0x00, 0xE0, 0xF3, 0x75, 0x12, 0x00]);
+        assert_eq!(Mode::SChip, Quirks::detect(&rom).mode);
+
+        // the flags only exist for the first 8 registers
+        let rom = Rom::from_bytes("DATA", &[0xF9, 0x85]);
+        assert_eq!(Mode::Chip8, Quirks::detect(&rom).mode);
+    }
+}
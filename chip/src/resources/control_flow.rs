@@ -0,0 +1,170 @@
+//! The static control flow analysis of a [`rom`](super::Rom).
+
+use std::{
+    collections::{BTreeSet, VecDeque},
+    convert::TryFrom,
+};
+
+use super::Rom;
+use crate::{
+    definitions::{cpu, memory},
+    opcode::{self, Opcodes, Zero},
+    quirks::Quirks,
+};
+
+/// How the control is passed from one block to another.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EdgeKind {
+    /// The execution continues with the following opcode.
+    Fallthrough,
+    /// A `1NNN` jump.
+    Jump,
+    /// A `2NNN` subroutine call, the return is the fallthrough of the call.
+    Call,
+    /// A skip opcode (e.g. `3XNN`) passes over the following opcode.
+    Skip,
+    /// A `BNNN` jump, where the target is only the base address, as `V0` is added at
+    /// runtime.
+    Indirect,
+}
+
+/// A sequence of opcodes, that is always executed from the start to the end.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BasicBlock {
+    /// The address of the first opcode.
+    pub start: usize,
+    /// The address after the last opcode.
+    pub end: usize,
+}
+
+/// A transfer of the control from the end of a block to an address.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Edge {
+    /// The start address of the block, that passes the control.
+    pub from: usize,
+    /// The target address, this is the start of a block, if the target lies within the
+    /// rom.
+    pub to: usize,
+    /// How the control is passed.
+    pub kind: EdgeKind,
+}
+
+/// The basic blocks reachable from the entry point and the edges between them.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ControlFlowGraph {
+    /// The blocks ordered by their address.
+    pub blocks: Vec<BasicBlock>,
+    /// The edges ordered by the address of their block.
+    pub edges: Vec<Edge>,
+}
+
+impl Rom {
+    /// Will build the control flow graph of the program, by following the jumps, calls and
+    /// skips from the [`PROGRAM_COUNTER`](cpu::PROGRAM_COUNTER).
+    ///
+    /// This is a static analysis, so that the targets of `BNNN` are not followed, as they
+    /// depend on `V0`, and code only reached that way is missing from the graph. Opcodes,
+    /// that are not supported in the mode of the quirks, end a block without a successor.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use chip::{quirks::Quirks, resources::{BasicBlock, Rom}};
+    /// // LD V0, 1; SE V0, 1; JP 0x200; CLS
+    /// let rom = Rom::from_bytes("LOOP", &[0x60, 0x01, 0x30, 0x01, 0x12, 0x00, 0x00, 0xE0]);
+    /// let graph = rom.control_flow_graph(&Quirks::default());
+    /// assert_eq!(
+    ///     vec![
+    ///         BasicBlock { start: 0x200, end: 0x204 },
+    ///         BasicBlock { start: 0x204, end: 0x206 },
+    ///         BasicBlock { start: 0x206, end: 0x208 },
+    ///     ],
+    ///     graph.blocks
+    /// );
+    /// ```
+    pub fn control_flow_graph(&self, quirks: &Quirks) -> ControlFlowGraph {
+        let decode = |address: usize| {
+            let pointer = address.checked_sub(cpu::PROGRAM_COUNTER)?;
+            let value = opcode::build_opcode(self.get_data(), pointer).ok()?;
+            Opcodes::try_from(value)
+                .ok()
+                .filter(|opcode| quirks.mode.supports(opcode))
+        };
+
+        // find all the reachable opcodes and the addresses starting a block
+        let mut visited = BTreeSet::new();
+        let mut leaders = BTreeSet::from([cpu::PROGRAM_COUNTER]);
+        let mut queue = VecDeque::from([cpu::PROGRAM_COUNTER]);
+        while let Some(address) = queue.pop_front() {
+            if !visited.insert(address) {
+                continue;
+            }
+            let opcode = match decode(address) {
+                Some(opcode) => opcode,
+                None => continue,
+            };
+            let successors = successors(address, &opcode);
+            for &(target, kind) in &successors {
+                if kind != EdgeKind::Fallthrough || successors.len() > 1 {
+                    leaders.insert(target);
+                }
+                if kind != EdgeKind::Indirect {
+                    queue.push_back(target);
+                }
+            }
+        }
+
+        // split the reachable opcodes into the blocks
+        let mut graph = ControlFlowGraph::default();
+        let decoded: Vec<_> = visited
+            .into_iter()
+            .filter_map(|address| decode(address).map(|opcode| (address, opcode)))
+            .collect();
+        let mut start = None;
+        for (i, &(address, opcode)) in decoded.iter().enumerate() {
+            let block_start = *start.get_or_insert(address);
+            let successors = successors(address, &opcode);
+            let next = address + memory::opcodes::SIZE;
+            let continues = successors == [(next, EdgeKind::Fallthrough)]
+                && !leaders.contains(&next)
+                && decoded.get(i + 1).map(|&(address, _)| address) == Some(next);
+            if continues {
+                continue;
+            }
+
+            graph.blocks.push(BasicBlock {
+                start: block_start,
+                end: next,
+            });
+            graph
+                .edges
+                .extend(successors.into_iter().map(|(to, kind)| Edge {
+                    from: block_start,
+                    to,
+                    kind,
+                }));
+            start = None;
+        }
+
+        graph
+    }
+}
+
+/// Will return the addresses, that the opcode at the given address passes the control to.
+fn successors(address: usize, opcode: &Opcodes) -> Vec<(usize, EdgeKind)> {
+    let next = address + memory::opcodes::SIZE;
+    let skip = next + memory::opcodes::SIZE;
+    match opcode {
+        Opcodes::Zero(Zero::Return | Zero::Exit) => Vec::new(),
+        Opcodes::One(opcode::One { nnn }) => vec![(*nnn, EdgeKind::Jump)],
+        Opcodes::Two(opcode::Two { nnn }) => {
+            vec![(*nnn, EdgeKind::Call), (next, EdgeKind::Fallthrough)]
+        }
+        Opcodes::B(opcode::Eleven { nnn }) => vec![(*nnn, EdgeKind::Indirect)],
+        Opcodes::Three(_)
+        | Opcodes::Four(_)
+        | Opcodes::Five(_)
+        | Opcodes::Nine(_)
+        | Opcodes::E(_) => vec![(next, EdgeKind::Fallthrough), (skip, EdgeKind::Skip)],
+        _ => vec![(next, EdgeKind::Fallthrough)],
+    }
+}
@@ -0,0 +1,89 @@
+//! The listing of the opcodes stored in a [`rom`](super::Rom).
+
+use std::{convert::TryFrom, fmt::Write};
+
+use super::Rom;
+use crate::{
+    definitions::{cpu, memory},
+    opcode::{self, Mode, Opcode, OpcodeInfo, Opcodes},
+    quirks::Quirks,
+};
+
+impl Rom {
+    /// Will disassemble the whole rom into a listing, with one line per opcode
+    /// containing the address, the raw bytes and the mnemonic, e.g.
+    /// `0x0200  00 E0  CLS`.
+    ///
+    /// Like [`validate`](Self::validate) this is a linear sweep over the program, so that
+    /// data stored within the program is decoded as well. Words, that are not supported
+    /// in the mode of the quirks, are listed as `DW` data words.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use chip::{quirks::Quirks, resources::Rom};
+    /// let rom = Rom::from_bytes("EXAMPLE", &[0x00, 0xE0, 0xD1, 0x25, 0x01, 0x23]);
+    /// assert_eq!(
+    ///     "0x0200  00 E0  CLS\n\
+    ///      0x0202  D1 25  DRW V1, V2, 5\n\
+    ///      0x0204  01 23  DW 0x0123\n",
+    ///     rom.disassembly(&Quirks::default())
+    /// );
+    /// ```
+    pub fn disassembly(&self, quirks: &Quirks) -> String {
+        let mut listing = String::new();
+
+        for (pointer, bytes) in self
+            .get_data()
+            .chunks_exact(memory::opcodes::SIZE)
+            .enumerate()
+            .map(|(i, bytes)| (i * memory::opcodes::SIZE, bytes))
+        {
+            let address = cpu::PROGRAM_COUNTER + pointer;
+            let value = Opcode::from_be_bytes([bytes[0], bytes[1]]);
+            let mnemonic = format_opcode(value, quirks.mode)
+                .map(|(_, mnemonic)| mnemonic)
+                .unwrap_or_else(|| format!("DW {:#06X}", value));
+            let _ = writeln!(
+                listing,
+                "{:#06X}  {:02X} {:02X}  {}",
+                address, bytes[0], bytes[1], mnemonic
+            );
+        }
+
+        listing
+    }
+}
+
+/// Will decode the opcode and format its mnemonic with the operands filled in, e.g.
+/// `DRW V1, V2, 5`. Returns `None`, if the value is not an opcode supported in the mode.
+pub(crate) fn format_opcode(value: Opcode, mode: Mode) -> Option<(Opcodes, String)> {
+    let opcode = Opcodes::try_from(value)
+        .ok()
+        .filter(|opcode| mode.supports(opcode))?;
+    let info = opcode::opcode_info(value, mode)?;
+    Some((opcode, mnemonic(&info, value)))
+}
+
+/// Will fill the operands of the opcode into the mnemonic of the catalog.
+fn mnemonic(info: &OpcodeInfo, value: Opcode) -> String {
+    let (name, operands) = match info.mnemonic.split_once(' ') {
+        None => return info.mnemonic.to_string(),
+        Some(split) => split,
+    };
+
+    let nibble = |shift: usize| (value >> shift) & 0xF;
+    let operands: Vec<_> = operands
+        .split(',')
+        .map(str::trim)
+        .map(|template| match template {
+            "VX" => format!("V{:X}", nibble(8)),
+            "VY" => format!("V{:X}", nibble(4)),
+            "N" => format!("{}", nibble(0)),
+            "NN" => format!("{:#04X}", value & 0xFF),
+            "NNN" => format!("{:#05X}", value & 0xFFF),
+            keyword => keyword.to_string(),
+        })
+        .collect();
+
+    format!("{} {}", name, operands.join(", "))
+}
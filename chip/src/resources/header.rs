@@ -0,0 +1,127 @@
+//! The optional metadata header stored in front of a [`rom`](super::Rom).
+//!
+//! The header has the following layout, all the values are stored as single bytes:
+//!
+//! | Offset  | Size | Content                                                |
+//! |---------|------|--------------------------------------------------------|
+//! | `0`     | `4`  | the magic bytes `CH8H`                                 |
+//! | `4`     | `1`  | the quirk flags                                        |
+//! | `5`     | `1`  | the length `N` of the title                            |
+//! | `6`     | `N`  | the title encoded as UTF-8                             |
+//! | `6 + N` |      | the program, which is loaded at `0x200`                |
+//!
+//! The quirk flags are:
+//! - bit `0`: the [`SChip`](Mode::SChip) instruction set is used
+//! - bits `1-2`: the handling of `0NNN`, `0` is [`Error`](SysCall::Error), `1` is
+//!   [`Ignore`](SysCall::Ignore) and `2` is [`Callback`](SysCall::Callback)
+//!
+//! All other bits are reserved and have to be `0`.
+
+use thiserror::Error;
+
+use super::Rom;
+use crate::{
+    opcode::Mode,
+    quirks::{Quirks, SysCall},
+};
+
+/// The bytes marking the start of a header.
+pub const MAGIC: [u8; 4] = *b"CH8H";
+
+/// The size of the header without the title.
+const FIXED_SIZE: usize = MAGIC.len() + 2;
+
+/// The flag selecting the SuperChip instruction set.
+const FLAG_SCHIP: u8 = 0b0000_0001;
+/// The position of the bits selecting the handling of `0NNN`.
+const SYS_CALL_SHIFT: u8 = 1;
+/// The bits selecting the handling of `0NNN`.
+const SYS_CALL_MASK: u8 = 0b0000_0110;
+
+/// The problems found while parsing a header.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum HeaderError {
+    #[error("The header ends after {0} bytes, before the title is complete.")]
+    Truncated(usize),
+    #[error("The title is not valid UTF-8.")]
+    InvalidTitle,
+    #[error("The quirk flags {0:#04X?} contain reserved or unknown values.")]
+    InvalidFlags(u8),
+}
+
+/// The metadata stored in the header of a rom.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct RomMetadata {
+    /// The title of the program.
+    pub title: String,
+    /// The behaviours of the interpreter the program was written against.
+    pub quirks: Quirks,
+}
+
+impl Rom {
+    /// Will generate a new rom from the given data, which might start with the
+    /// [header](crate::resources::header) containing the title and the quirks of the
+    /// program. Data without the [`MAGIC`] bytes is loaded like
+    /// [`from_bytes`](Self::from_bytes) and has no metadata.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use chip::{opcode::Mode, resources::Rom};
+    /// let data = [b'C', b'H', b'8', b'H', 0x01, 0x02, b'H', b'I', 0x00, 0xE0];
+    /// let (rom, metadata) = Rom::from_bytes_with_header("HI", &data).unwrap();
+    /// let metadata = metadata.unwrap();
+    /// assert_eq!("HI", metadata.title);
+    /// assert_eq!(Mode::SChip, metadata.quirks.mode);
+    /// assert_eq!(&[0x00, 0xE0], rom.get_data());
+    /// ```
+    pub fn from_bytes_with_header(
+        name: &str,
+        data: &[u8],
+    ) -> Result<(Self, Option<RomMetadata>), HeaderError> {
+        if !data.starts_with(&MAGIC) {
+            return Ok((Self::from_bytes(name, data), None));
+        }
+        if data.len() < FIXED_SIZE {
+            return Err(HeaderError::Truncated(data.len()));
+        }
+
+        let flags = data[MAGIC.len()];
+        let title_len = data[MAGIC.len() + 1] as usize;
+        let program = FIXED_SIZE + title_len;
+        let title = data
+            .get(FIXED_SIZE..program)
+            .ok_or(HeaderError::Truncated(data.len()))?;
+        let title = std::str::from_utf8(title).map_err(|_| HeaderError::InvalidTitle)?;
+
+        let metadata = RomMetadata {
+            title: title.to_string(),
+            quirks: parse_flags(flags)?,
+        };
+        Ok((Self::from_bytes(name, &data[program..]), Some(metadata)))
+    }
+}
+
+/// Will decode the quirk flags of the header.
+fn parse_flags(flags: u8) -> Result<Quirks, HeaderError> {
+    if flags & !(FLAG_SCHIP | SYS_CALL_MASK) != 0 {
+        return Err(HeaderError::InvalidFlags(flags));
+    }
+
+    let mode = if flags & FLAG_SCHIP != 0 {
+        Mode::SChip
+    } else {
+        Mode::Chip8
+    };
+    let sys_call = match (flags & SYS_CALL_MASK) >> SYS_CALL_SHIFT {
+        0 => SysCall::Error,
+        1 => SysCall::Ignore,
+        2 => SysCall::Callback,
+        _ => return Err(HeaderError::InvalidFlags(flags)),
+    };
+
+    Ok(Quirks {
+        mode,
+        sys_call,
+        ..Default::default()
+    })
+}
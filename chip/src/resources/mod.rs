@@ -9,6 +9,24 @@ use std::{
 };
 use zip::{read::ZipArchive, result::ZipResult};
 
+use crate::{
+    chip8::ChipSet,
+    definitions::memory,
+    quirks::Quirks,
+    timer::{TimedWorker, TimerCallback},
+    ChipError,
+};
+
+mod control_flow;
+mod disassembly;
+pub mod header;
+mod validate;
+
+pub use control_flow::{BasicBlock, ControlFlowGraph, Edge, EdgeKind};
+pub(crate) use disassembly::format_opcode;
+pub use header::{HeaderError, RomMetadata};
+pub use validate::{RomIssue, RomIssueKind};
+
 /// Contains all the available roms needed for running the games
 /// in a ZIP archive.
 const ROM_ARCHIVE: &[u8] = std::include_bytes!("c8games.zip");
@@ -34,6 +52,15 @@ impl RomArchives<'_> {
         Default::default()
     }
 
+    /// Will return the quirks the roms of the archive are run with. Some of them (e.g.
+    /// `MISSILE`) execute code at odd addresses, so that the alignment is not enforced.
+    pub fn quirks() -> Quirks {
+        Quirks {
+            enforce_alignment: false,
+            ..Default::default()
+        }
+    }
+
     /// Will return all the rom names available to be chosen
     pub fn file_names(&self) -> Vec<&str> {
         self.archive.file_names().collect()
@@ -42,16 +69,43 @@ impl RomArchives<'_> {
     // Will decompress the information from the zip archive
     pub fn get_file_data(&mut self, name: &str) -> ZipResult<Rom> {
         let mut file = self.archive.by_name(name)?;
-        // there might be a case where there is an uneven amount of
-        // data entries adding one for simplicty.
-        let size = (file.size() + file.size() % 2) as _;
-
-        let mut data = vec![0; size];
+        let mut data = vec![0; file.size() as _];
         // this result can be ignored as the included archive
         // will definitely contain data for if the file is included
         file.read_exact(&mut data)?;
         Ok(Rom::new(name, data))
     }
+
+    /// Will extract the rom with the given name and create a chip running it with the
+    /// [`quirks`](Self::quirks) of the archive.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use chip::{chip8::ChipSet, resources::RomArchives, timer::{NoCallback, Worker}};
+    /// let chip: ChipSet<Worker, NoCallback> = RomArchives::new().load("PONG").unwrap();
+    /// ```
+    pub fn load<W, S>(&mut self, name: &str) -> Result<ChipSet<W, S>, ChipError>
+    where
+        W: TimedWorker,
+        S: TimerCallback + 'static,
+    {
+        let rom = self.get_file_data(name)?;
+        let mut chip = ChipSet::try_new(rom)?;
+        chip.set_quirks(Self::quirks());
+        Ok(chip)
+    }
+}
+
+/// The byte order of the opcodes stored in a rom.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ByteOrder {
+    /// The most significant byte of an opcode is stored first, this is the
+    /// standard Chip8 layout.
+    #[default]
+    BigEndian,
+    /// The least significant byte of an opcode is stored first, as found in some
+    /// byte-swapped rom dumps.
+    LittleEndian,
 }
 
 #[derive(Clone)]
@@ -63,18 +117,50 @@ pub struct Rom {
     /// stored as a u8 slice on the heap
     /// uses a box for simple execution
     data: Vec<u8>,
+    /// If the given data had an odd length and was padded with a trailing zero byte.
+    padded: bool,
 }
 
 impl Rom {
-    /// Will generate a new rom based of the given data
-    fn new(name: &str, data: Vec<u8>) -> Self {
+    /// Will generate a new rom based of the given data.
+    ///
+    /// Every opcode is [`2 bytes`](memory::opcodes::SIZE) long, so that data of an odd
+    /// length is padded with a trailing zero byte. This way the last opcode can always
+    /// be decoded and every consumer of [`get_data`](Self::get_data) can rely on whole
+    /// opcodes.
+    fn new(name: &str, mut data: Vec<u8>) -> Self {
+        let padded = !data.len().is_multiple_of(memory::opcodes::SIZE);
+        if padded {
+            data.push(0);
+        }
         Rom {
             name: name.to_string(),
             data,
+            padded,
         }
     }
 
-    /// Will return a slice internal values of the given data
+    /// Will generate a new rom from the given big endian data, data of an odd length is
+    /// padded with a trailing zero byte to whole opcodes.
+    pub fn from_bytes(name: &str, data: &[u8]) -> Self {
+        Self::from_bytes_with_order(name, data, ByteOrder::BigEndian)
+    }
+
+    /// Will generate a new rom from the given data, where the opcodes are stored
+    /// in the given byte order. Little endian data is swapped on load, so that
+    /// the rom always contains big endian opcodes.
+    pub fn from_bytes_with_order(name: &str, data: &[u8], order: ByteOrder) -> Self {
+        let mut rom = Self::new(name, data.to_vec());
+        if let ByteOrder::LittleEndian = order {
+            for opcode in rom.data.chunks_exact_mut(memory::opcodes::SIZE) {
+                opcode.swap(0, 1);
+            }
+        }
+        rom
+    }
+
+    /// Will return a slice internal values of the given data, which always has an even
+    /// length, see [`from_bytes`](Self::from_bytes).
     pub fn get_data(&self) -> &[u8] {
         &self.data
     }
@@ -83,12 +169,30 @@ impl Rom {
     pub fn get_name(&self) -> &str {
         &self.name
     }
+
+    /// Will return the size of the rom data in bytes.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Will check if the rom contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::RomArchives;
-    use crate::opcode::{build_opcode, Opcode};
+    use super::{
+        BasicBlock, ByteOrder, Edge, EdgeKind, HeaderError, Rom, RomArchives, RomIssue,
+        RomIssueKind,
+    };
+    use crate::chip8::ChipSet;
+    use crate::definitions::memory;
+    use crate::opcode::{build_opcode, Mode, Opcode, Operation};
+    use crate::quirks::{Quirks, SysCall};
+    use crate::timer::{NoCallback, Worker};
+    use crate::ChipError;
     const RAW_ROM_DATA: [Opcode; 192] = [
         0x00E0, 0x6C00, 0x4C00, 0x6E0F, 0xA203, 0x6020, 0xF055, 0x00E0, 0x22BE, 0x2276, 0x228E,
         0x225E, 0x2246, 0x1210, 0x6100, 0x6217, 0x6304, 0x4110, 0x00EE, 0xA2E8, 0xF11E, 0xF065,
@@ -153,6 +257,209 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load() {
+        let mut ra = RomArchives::new();
+        let chip: Result<ChipSet<Worker, NoCallback>, _> = ra.load(ROM_NAMES[0]);
+        assert!(chip.is_ok());
+
+        let chip: Result<ChipSet<Worker, NoCallback>, _> = ra.load("MISSING");
+        assert!(matches!(chip, Err(ChipError::Archive(_))));
+
+        let rom = Rom::from_bytes("LARGE", &[0; memory::SIZE]);
+        let chip: Result<ChipSet<Worker, NoCallback>, _> = ChipSet::try_new(rom);
+        assert!(matches!(
+            chip,
+            Err(ChipError::RomSize {
+                size: memory::SIZE,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    /// every rom of the archive has to run with the quirks the frontends use
+    fn test_archive_roms_run() {
+        let mut ra = RomArchives::new();
+        for name in ROM_NAMES {
+            let mut chip: ChipSet<Worker, NoCallback> = ra.load(name).unwrap();
+            assert_eq!(&RomArchives::quirks(), chip.quirks());
+            let mut waiting = false;
+            for cycle in 0..20_000 {
+                // like a frontend, a waiting chip is only stepped once a key was pressed
+                chip.set_keys_from(|key| waiting && key == cycle % 16);
+                match chip.step() {
+                    Ok(Operation::Exit) => break,
+                    Ok(operation) => waiting = operation == Operation::Wait,
+                    Err(err) => panic!("{} failed in cycle {}: {}", name, cycle, err),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_byte_order() {
+        let data = [0xE0, 0x00, 0xEE, 0x00];
+        let rom = Rom::from_bytes("test", &data);
+        assert_eq!(&data, rom.get_data());
+        assert_eq!("test", rom.get_name());
+
+        let rom = Rom::from_bytes_with_order("test", &data, ByteOrder::LittleEndian);
+        assert_eq!(&[0x00, 0xE0, 0x00, 0xEE], rom.get_data());
+        assert_eq!(4, rom.len());
+        assert!(!rom.is_empty());
+        assert!(Rom::from_bytes("EMPTY", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_from_bytes_padding() {
+        // LD V0, 0x12; JP 0x3 (missing the last byte)
+        let rom = Rom::from_bytes("odd", &[0x60, 0x12, 0x13]);
+        assert_eq!(&[0x60, 0x12, 0x13, 0x00], rom.get_data());
+        assert_eq!(4, rom.len());
+        assert_eq!(Ok(0x1300), build_opcode(rom.get_data(), 2));
+
+        let rom = Rom::from_bytes_with_order("odd", &[0x12, 0x60, 0x13], ByteOrder::LittleEndian);
+        assert_eq!(&[0x60, 0x12, 0x00, 0x13], rom.get_data());
+
+        let mut chip: ChipSet<Worker, NoCallback> = ChipSet::new(Rom::from_bytes("odd", &[0x13]));
+        assert_eq!(Ok(Operation::None), chip.step());
+    }
+
+    #[test]
+    fn test_validate() {
+        let quirks = Quirks::default();
+        let rom = Rom::new("test", vec![0x00, 0xE0, 0x12, 0x00]);
+        assert_eq!(Ok(()), rom.validate(&quirks));
+
+        let data = vec![
+            0x00, 0xE0, // valid
+            0x01, 0x23, // unsupported
+            0x11, 0x00, // jump before the rom
+            0xAF, 0xFF, // index at the end of the memory
+            0x12, 0x00, // valid
+            0x00, // padded to the unsupported 0x0000
+        ];
+        let rom = Rom::new("test", data);
+        let issue = |address, kind| RomIssue { address, kind };
+        assert_eq!(
+            Err(vec![
+                issue(0x202, RomIssueKind::UnsupportedOpcode(0x0123)),
+                issue(0x204, RomIssueKind::JumpOutOfBounds(0x100)),
+                issue(0x206, RomIssueKind::IndexOutOfBounds(0xFFF)),
+                issue(0x20A, RomIssueKind::UnsupportedOpcode(0x0000)),
+                issue(0x20A, RomIssueKind::OddLength(11)),
+            ]),
+            rom.validate(&quirks)
+        );
+    }
+
+    #[test]
+    fn test_disassembly() {
+        let data = [0x6A, 0x2A, 0xF3, 0x75, 0xA3, 0x45, 0x00];
+        let rom = Rom::from_bytes("test", &data);
+        assert_eq!(
+            "0x0200  6A 2A  LD VA, 0x2A\n\
+             0x0202  F3 75  DW 0xF375\n\
+             0x0204  A3 45  LD I, 0x345\n\
+             0x0206  00 00  DW 0x0000\n",
+            rom.disassembly(&Quirks::default())
+        );
+
+        let quirks = Quirks {
+            mode: Mode::SChip,
+            ..Default::default()
+        };
+        let listing = rom.disassembly(&quirks);
+        assert_eq!(Some("0x0202  F3 75  LD R, V3"), listing.lines().nth(1));
+    }
+
+    #[test]
+    fn test_control_flow_graph() {
+        let data = [
+            0x22, 0x08, // 0x200: CALL 0x208
+            0x3A, 0x00, // 0x202: SE VA, 0
+            0x12, 0x00, // 0x204: JP 0x200
+            0x12, 0x06, // 0x206: JP 0x206
+            0x60, 0x01, // 0x208: LD V0, 1
+            0x00, 0xEE, // 0x20A: RET
+            0xB3, 0x00, // 0x20C: JP V0, 0x300 (unreachable)
+        ];
+        let rom = Rom::from_bytes("test", &data);
+        let graph = rom.control_flow_graph(&Quirks::default());
+
+        let block = |start, end| BasicBlock { start, end };
+        assert_eq!(
+            vec![
+                block(0x200, 0x202),
+                block(0x202, 0x204),
+                block(0x204, 0x206),
+                block(0x206, 0x208),
+                block(0x208, 0x20C),
+            ],
+            graph.blocks
+        );
+
+        let edge = |from, to, kind| Edge { from, to, kind };
+        assert_eq!(
+            vec![
+                edge(0x200, 0x208, EdgeKind::Call),
+                edge(0x200, 0x202, EdgeKind::Fallthrough),
+                edge(0x202, 0x204, EdgeKind::Fallthrough),
+                edge(0x202, 0x206, EdgeKind::Skip),
+                edge(0x204, 0x200, EdgeKind::Jump),
+                edge(0x206, 0x206, EdgeKind::Jump),
+            ],
+            graph.edges
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_with_header() {
+        let program = [0x00, 0xE0, 0x12, 0x00];
+
+        // without a header
+        let (rom, metadata) = Rom::from_bytes_with_header("test", &program).unwrap();
+        assert_eq!(&program, rom.get_data());
+        assert_eq!(None, metadata);
+
+        let mut data = b"CH8H\x05\x04PONG".to_vec();
+        data.extend_from_slice(&program);
+        let (rom, metadata) = Rom::from_bytes_with_header("test", &data).unwrap();
+        let metadata = metadata.unwrap();
+        assert_eq!(&program, rom.get_data());
+        assert_eq!("test", rom.get_name());
+        assert_eq!("PONG", metadata.title);
+        assert_eq!(Mode::SChip, metadata.quirks.mode);
+        assert_eq!(SysCall::Callback, metadata.quirks.sys_call);
+
+        // an empty title and program
+        let (rom, metadata) = Rom::from_bytes_with_header("test", b"CH8H\x00\x00").unwrap();
+        assert!(rom.is_empty());
+        assert_eq!(Some(Default::default()), metadata);
+
+        assert_eq!(
+            Err(HeaderError::Truncated(5)),
+            Rom::from_bytes_with_header("test", b"CH8H\x00").map(|_| ())
+        );
+        assert_eq!(
+            Err(HeaderError::Truncated(8)),
+            Rom::from_bytes_with_header("test", b"CH8H\x00\x04PO").map(|_| ())
+        );
+        assert_eq!(
+            Err(HeaderError::InvalidTitle),
+            Rom::from_bytes_with_header("test", b"CH8H\x00\x01\xFF").map(|_| ())
+        );
+        assert_eq!(
+            Err(HeaderError::InvalidFlags(0x06)),
+            Rom::from_bytes_with_header("test", b"CH8H\x06\x00").map(|_| ())
+        );
+        assert_eq!(
+            Err(HeaderError::InvalidFlags(0x80)),
+            Rom::from_bytes_with_header("test", b"CH8H\x80\x00").map(|_| ())
+        );
+    }
+
     #[test]
     fn test_file_names() {
         let ra = RomArchives::new();
@@ -0,0 +1,107 @@
+//! The static checks run over a [`rom`](super::Rom) before it is executed.
+
+use std::{convert::TryFrom, fmt};
+use thiserror::Error;
+
+use super::Rom;
+use crate::{
+    definitions::{cpu, memory},
+    opcode::{self, Opcode, Opcodes},
+    quirks::Quirks,
+};
+
+/// The largest amount of bytes, that a single opcode can access relative to `I`.
+/// (`FXXX` with `X = F` reads or writes `16` registers)
+const MAX_INDEX_ACCESS: usize = cpu::register::SIZE;
+
+/// The kind of problem found in a rom.
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RomIssueKind {
+    #[error("An unsupported opcode {0:#06X?} was found.")]
+    UnsupportedOpcode(Opcode),
+    #[error("The jump target {0:#06X?} is outside of the loaded rom.")]
+    JumpOutOfBounds(usize),
+    #[error("The index {0:#06X?} might lead to memory accesses past the end of the memory.")]
+    IndexOutOfBounds(usize),
+    #[error("The rom has an odd length of {0} bytes, so that its last opcode is incomplete.")]
+    OddLength(usize),
+}
+
+/// A single problem found in a rom.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RomIssue {
+    /// The memory address of the offending opcode.
+    pub address: usize,
+    /// What kind of problem was found.
+    pub kind: RomIssueKind,
+}
+
+impl fmt::Display for RomIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#06X}: {}", self.address, self.kind)
+    }
+}
+
+impl Rom {
+    /// Will statically check the rom for problems before it is run.
+    ///
+    /// The check is a linear sweep over the program, so that data stored within the
+    /// program (e.g. sprites) might be reported as unsupported opcodes. The reported
+    /// issues are:
+    /// - unsupported opcodes
+    /// - jumps and calls to addresses outside of the loaded rom
+    /// - indices that might lead to accesses past the end of the memory
+    /// - an odd length of the loaded data, which was padded with a zero byte, see
+    ///   [`from_bytes`](Self::from_bytes)
+    pub fn validate(&self, quirks: &Quirks) -> Result<(), Vec<RomIssue>> {
+        let data = self.get_data();
+        let end = cpu::PROGRAM_COUNTER + self.len();
+        let mut issues = Vec::new();
+        let mut issue = |pointer: usize, kind| {
+            issues.push(RomIssue {
+                address: cpu::PROGRAM_COUNTER + pointer,
+                kind,
+            })
+        };
+
+        let in_rom = |address: usize| (cpu::PROGRAM_COUNTER..end).contains(&address);
+
+        for (pointer, bytes) in data.chunks_exact(memory::opcodes::SIZE).enumerate() {
+            let pointer = pointer * memory::opcodes::SIZE;
+            let value = Opcode::from_be_bytes([bytes[0], bytes[1]]);
+
+            let decoded = Opcodes::try_from(value)
+                .ok()
+                .filter(|opcode| quirks.mode.supports(opcode));
+
+            match decoded {
+                None => issue(pointer, RomIssueKind::UnsupportedOpcode(value)),
+                Some(Opcodes::One(opcode::One { nnn }) | Opcodes::Two(opcode::Two { nnn }))
+                    if !in_rom(nnn) =>
+                {
+                    issue(pointer, RomIssueKind::JumpOutOfBounds(nnn));
+                }
+                Some(Opcodes::B(opcode::Eleven { nnn }))
+                    if !in_rom(nnn) || nnn + u8::MAX as usize >= memory::SIZE =>
+                {
+                    issue(pointer, RomIssueKind::JumpOutOfBounds(nnn));
+                }
+                Some(Opcodes::A(opcode::Ten { nnn })) if nnn + MAX_INDEX_ACCESS > memory::SIZE => {
+                    issue(pointer, RomIssueKind::IndexOutOfBounds(nnn));
+                }
+                Some(_) => {}
+            }
+        }
+
+        if self.padded {
+            let last = self.len() - memory::opcodes::SIZE;
+            issue(last, RomIssueKind::OddLength(self.len() - 1));
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+}
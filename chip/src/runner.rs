@@ -2,13 +2,20 @@
 //!
 //! Handles part of the execution and interaction with the display, keyboard and sound system.
 use crate::{
-    chip8::ChipSet,
+    chip8::{ChipSet, Snapshot},
+    definitions::display,
     devices::{DisplayCommands, KeyboardCommands},
     opcode::Operation,
+    quirks::Quirks,
     resources::Rom,
     timer::{TimedWorker, TimerCallback},
-    ProcessError,
+    ChipError, ProcessError, SnapshotError,
 };
+use std::collections::VecDeque;
+use web_time::Instant;
+
+/// The amount of frames over which the [`RunnerStats`](RunnerStats) are measured.
+pub const STATS_WINDOW: usize = 60;
 
 /// A collection of all the important interfaces.
 /// Is primarily used to simplify the crate api.
@@ -25,8 +32,18 @@ where
     keyboard: K,
     /// The all important chipset implementation.
     chipset: Option<ChipSet<W, S>>,
+    /// The quirks every loaded chipset runs with.
+    quirks: Quirks,
     /// The next run operation.
     operation: Operation,
+    /// The amount of frames skipped by [`run_frame`](run_frame) after every drawn one.
+    frame_skip: usize,
+    /// The amount of frames run by [`run_frame`](run_frame).
+    frame: usize,
+    /// If the display changed since it was last drawn by [`run_frame`](run_frame).
+    dirty: bool,
+    /// The measured execution speed.
+    stats: RunnerStats,
 }
 
 impl<D, K, W, S> Controller<D, K, W, S>
@@ -42,7 +59,12 @@ where
             display: dis,
             keyboard: key,
             chipset: None,
+            quirks: Quirks::default(),
             operation: Operation::None,
+            frame_skip: 0,
+            frame: 0,
+            dirty: false,
+            stats: RunnerStats::default(),
         }
     }
 
@@ -57,10 +79,40 @@ where
     }
 
     /// Set the controller's chipset.
-    pub fn set_rom(&mut self, rom: Rom) {
-        let chipset = ChipSet::with_keyboard(rom, self.keyboard.get_keyboard());
+    ///
+    /// Fails with [`RomSize`](ChipError::RomSize), if the rom does not fit into the
+    /// memory, in which case the previous chipset is kept.
+    pub fn set_rom(&mut self, rom: Rom) -> Result<(), ChipError> {
+        let mut chipset = ChipSet::try_with_keyboard(rom, self.keyboard.get_keyboard())?;
+        chipset.set_quirks(self.quirks);
+        self.chipset = Some(chipset);
+        self.operation = Operation::None;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Will load the rom of the snapshot and restore the state of the snapshot, the
+    /// restored display is shown right away.
+    pub fn load_snapshot(&mut self, snapshot: Snapshot) -> Result<(), SnapshotError> {
+        let rom = Rom::from_bytes(&snapshot.rom_name, &snapshot.rom);
+        let mut chipset = ChipSet::with_keyboard(rom, self.keyboard.get_keyboard());
+        chipset.set_quirks(self.quirks);
+        chipset.restore(snapshot)?;
+        self.display.display(chipset.get_display());
         self.chipset = Some(chipset);
         self.operation = Operation::None;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Will set the quirks, that the roms loaded from now on run with, the chipset
+    /// currently running is switched over as well. E.g. the roms of the
+    /// [`archive`](crate::resources::RomArchives::quirks) need their own quirks.
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+        if let Some(chipset) = self.chipset.as_mut() {
+            chipset.set_quirks(quirks);
+        }
     }
 
     /// Remove the rom and resets the internal state of the chip to the new state.
@@ -69,6 +121,18 @@ where
         self.operation = Operation::None;
     }
 
+    /// Will present the double buffered display of the chip and show it, see
+    /// [`ChipSet::present`](ChipSet::present).
+    pub fn present(&mut self) -> Result<(), ProcessError> {
+        let chip = self
+            .chipset
+            .as_mut()
+            .ok_or(ProcessError::UninitializedChipset)?;
+        chip.present();
+        self.display.display(chip.get_display());
+        Ok(())
+    }
+
     /// Get a reference to the controller's keyboard.
     pub fn keyboard(&mut self) -> &mut K {
         &mut self.keyboard
@@ -79,6 +143,34 @@ where
         &self.display
     }
 
+    /// Will return by how many output pixels a single pixel of the display is scaled
+    /// `(horizontally, vertically)`, so that it fills the
+    /// [`dimensions`](DisplayCommands::dimensions) of the display adapter.
+    /// The scale is at least `1`.
+    pub fn display_scale(&self) -> (usize, usize) {
+        let (width, height) = self.display.dimensions();
+        (
+            (width / display::HEIGHT).max(1),
+            (height / display::WIDTH).max(1),
+        )
+    }
+
+    /// Get the amount of frames skipped after every drawn frame.
+    pub fn frame_skip(&self) -> usize {
+        self.frame_skip
+    }
+
+    /// Will draw only every `frame_skip + 1`th frame run by [`run_frame`](run_frame),
+    /// by default every frame is drawn.
+    ///
+    /// The skipped frames still execute all their cycles and the timers keep on counting
+    /// down in the background, so that a game runs at the correct speed on a host, that
+    /// can not render every frame. The price is a less smooth animation, as the changes
+    /// of the skipped frames are only shown with the next drawn one.
+    pub fn set_frame_skip(&mut self, frame_skip: usize) {
+        self.frame_skip = frame_skip;
+    }
+
     /// Get a reference to the controller's operation.
     pub fn operation(&self) -> Operation {
         self.operation
@@ -88,6 +180,153 @@ where
     pub fn set_operation(&mut self, operation: Operation) {
         self.operation = operation;
     }
+
+    /// Get the measured execution speed, see [`RunnerStats`](RunnerStats).
+    pub fn stats(&self) -> &RunnerStats {
+        &self.stats
+    }
+
+    /// Will discard the measured execution speed, e.g. after the host was suspended.
+    pub fn reset_stats(&mut self) {
+        self.stats.reset();
+    }
+}
+
+/// The execution speed measured over the last [`STATS_WINDOW`](STATS_WINDOW) frames, so
+/// that a host can check if it reaches the target clock.
+///
+/// Every opcode executed by [`run`](run) and [`run_frame`](run_frame) is counted, while
+/// only [`run_frame`](run_frame) ends a frame. The opcodes executed by [`run`](run) are
+/// added to the frame ended next. All the rates are `0` until two frames were ended.
+#[derive(Debug, Default, Clone)]
+pub struct RunnerStats {
+    /// The end of every frame in the window with the opcodes executed during it, the
+    /// oldest first.
+    frames: VecDeque<(Instant, usize)>,
+    /// The opcodes executed since the end of the last frame.
+    cycles: usize,
+}
+
+impl RunnerStats {
+    /// Will return the measured amount of opcodes executed per second.
+    pub fn instructions_per_second(&self) -> f64 {
+        let cycles: usize = self.frames.iter().skip(1).map(|&(_, cycles)| cycles).sum();
+        self.per_second(cycles)
+    }
+
+    /// Will return the measured amount of frames ended per second.
+    pub fn frames_per_second(&self) -> f64 {
+        self.per_second(self.frames.len().saturating_sub(1))
+    }
+
+    /// Will return the average amount of opcodes executed per frame.
+    pub fn average_cycles_per_frame(&self) -> f64 {
+        if self.frames.is_empty() {
+            return 0.0;
+        }
+        let cycles: usize = self.frames.iter().map(|&(_, cycles)| cycles).sum();
+        cycles as f64 / self.frames.len() as f64
+    }
+
+    /// Will discard all the measurements.
+    pub fn reset(&mut self) {
+        self.frames.clear();
+        self.cycles = 0;
+    }
+
+    /// Will count a single executed opcode.
+    fn record_cycle(&mut self) {
+        self.cycles += 1;
+    }
+
+    /// Will end the current frame, dropping the oldest one outside of the window.
+    fn end_frame(&mut self) {
+        self.frames.push_back((Instant::now(), self.cycles));
+        self.cycles = 0;
+        // the window spans between the ends of its frames
+        if self.frames.len() > STATS_WINDOW + 1 {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Will return the rate of the amount over the time spanned by the window.
+    fn per_second(&self, amount: usize) -> f64 {
+        let span = match (self.frames.front(), self.frames.back()) {
+            (Some(&(first, _)), Some(&(last, _))) => last.duration_since(first).as_secs_f64(),
+            _ => return 0.0,
+        };
+        if span > 0.0 {
+            amount as f64 / span
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Runs two chipsets in lockstep, so that differences in their behaviour can be found.
+///
+/// To feed both chips the same input, create them with the same keyboard using
+/// [`ChipSet::with_keyboard`](ChipSet::with_keyboard).
+///
+/// Attention the random number generators of the chips are independent, so that the
+/// `CXNN` opcode will most likely lead to different displays.
+pub struct DiffRunner<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback,
+{
+    /// The first of the compared chipsets.
+    first: ChipSet<W, S>,
+    /// The second of the compared chipsets.
+    second: ChipSet<W, S>,
+    /// The amount of cycles that both chips ran.
+    cycles: usize,
+}
+
+impl<W, S> DiffRunner<W, S>
+where
+    W: TimedWorker,
+    S: TimerCallback + 'static,
+{
+    /// Creates a new runner comparing the given chipsets.
+    pub fn new(first: ChipSet<W, S>, second: ChipSet<W, S>) -> Self {
+        Self {
+            first,
+            second,
+            cycles: 0,
+        }
+    }
+
+    /// Get a reference to the first chipset.
+    pub fn first(&self) -> &ChipSet<W, S> {
+        &self.first
+    }
+
+    /// Get a reference to the second chipset.
+    pub fn second(&self) -> &ChipSet<W, S> {
+        &self.second
+    }
+
+    /// Get the amount of cycles that both chips ran.
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// Will step both chips for at most `max` cycles and return the first cycle after
+    /// which the displays of both chips differ, or `None` if they stayed the same.
+    pub fn run(&mut self, max: usize) -> Result<Option<usize>, ProcessError> {
+        for _ in 0..max {
+            let cycle = self.cycles;
+            self.first.step()?;
+            self.second.step()?;
+            self.cycles += 1;
+
+            if self.first.get_display() != self.second.get_display() {
+                return Ok(Some(cycle));
+            }
+        }
+        Ok(None)
+    }
 }
 
 /// The main function that has to be called every
@@ -101,6 +340,8 @@ pub fn run<D, K, W, S>(
         keyboard,
         chipset,
         operation,
+        stats,
+        ..
     }: &mut Controller<D, K, W, S>,
 ) -> Result<(), ProcessError>
 where
@@ -115,14 +356,25 @@ where
         return Ok(());
     }
 
+    // an exited program is not run any further
+    if *operation == Operation::Exit {
+        return Ok(());
+    }
+
     // Extract the chip from the chipset option
     let chip = chipset.as_mut().ok_or(ProcessError::UninitializedChipset)?;
 
+    // a paused chip does nothing at all
+    if chip.is_paused() {
+        return Ok(());
+    }
+
     // run chip
     *operation = chip.step()?;
+    stats.record_cycle();
 
     // Checks if we can redraw the screen after this or not.
-    if *operation == Operation::Draw {
+    if let Operation::Draw | Operation::Clear | Operation::Scroll { .. } = *operation {
         /* draw the screen */
         display.display(chip.get_display());
     }
@@ -130,6 +382,59 @@ where
     Ok(())
 }
 
+/// Will run a single logical frame of `cycles` opcodes, this function has to be called
+/// every frame (e.g. `60` times a second).
+///
+/// Unlike [`run`](run) the display is only drawn once at the end of the frame, if it
+/// changed, and only every [`frame_skip`](Controller::set_frame_skip)`+ 1`th frame.
+/// The frame stops early, if the chip waits for a key press or the program exited. A
+/// paused chip does nothing at all, the frame is neither counted nor drawn.
+pub fn run_frame<D, K, W, S>(
+    controller: &mut Controller<D, K, W, S>,
+    cycles: usize,
+) -> Result<(), ProcessError>
+where
+    D: DisplayCommands,
+    K: KeyboardCommands,
+    S: TimerCallback,
+    W: TimedWorker,
+{
+    let chip = controller
+        .chipset
+        .as_mut()
+        .ok_or(ProcessError::UninitializedChipset)?;
+
+    // a paused chip does nothing at all
+    if chip.is_paused() {
+        return Ok(());
+    }
+
+    for _ in 0..cycles {
+        if controller.operation == Operation::Exit
+            || (controller.operation == Operation::Wait && !controller.keyboard.was_pressed())
+        {
+            break;
+        }
+
+        controller.operation = chip.step()?;
+        controller.stats.record_cycle();
+        if let Operation::Draw | Operation::Clear | Operation::Scroll { .. } = controller.operation
+        {
+            controller.dirty = true;
+        }
+    }
+
+    controller.stats.end_frame();
+    let frame = controller.frame;
+    controller.frame = frame.wrapping_add(1);
+    if controller.dirty && frame.is_multiple_of(controller.frame_skip.saturating_add(1)) {
+        controller.display.display(chip.get_display());
+        controller.dirty = false;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -210,6 +515,8 @@ mod tests {
         let ka = KeyboardAdapter { ka: mock_keyboard };
 
         let mut controller: Controller<_, _, Worker, NoCallback> = Controller::new(da, ka);
+        assert_eq!((64, 32), controller.display().dimensions());
+        assert_eq!((1, 1), controller.display_scale());
 
         assert_eq!(
             Err(ProcessError::UninitializedChipset),
@@ -220,11 +527,154 @@ mod tests {
             .get_file_data(ROM_NAME)
             .expect("Something went wrong while extracting the rom");
 
-        controller.set_rom(rom);
+        // the quirks are kept for the roms loaded later on
+        let quirks = crate::resources::RomArchives::quirks();
+        controller.set_quirks(quirks);
+        controller.set_rom(rom).unwrap();
+        assert_eq!(&quirks, controller.chipset().as_ref().unwrap().quirks());
 
         assert_eq!(Ok(()), run(&mut controller));
-        assert_eq!(Operation::Draw, controller.operation());
+        assert_eq!(Operation::Clear, controller.operation());
 
         assert_eq!(Ok(()), run(&mut controller));
+
+        // a rom, that does not fit into the memory, keeps the running chip
+        let rom = Rom::from_bytes("LARGE", &[0; crate::definitions::memory::SIZE]);
+        assert!(matches!(
+            controller.set_rom(rom),
+            Err(ChipError::RomSize { .. })
+        ));
+        let chip = controller.chipset().as_ref().unwrap();
+        assert_eq!(ROM_NAME, chip.snapshot().rom_name);
+    }
+
+    #[test]
+    fn test_run_frame() {
+        let mut mock_display = MockInternalDCommands::new();
+        // the first draw and the one after the two skipped frames
+        mock_display.expect_display().times(2).return_const(());
+        let da = DisplayAdapter { da: mock_display };
+
+        let mut mock_keyboard = MockInternalKCommands::new();
+        mock_keyboard
+            .expect_get_keyboard()
+            .returning(|| Arc::new(RwLock::new(Keyboard::new())));
+        let ka = KeyboardAdapter { ka: mock_keyboard };
+
+        let mut controller: Controller<_, _, Worker, NoCallback> = Controller::new(da, ka);
+        assert_eq!(
+            Err(ProcessError::UninitializedChipset),
+            run_frame(&mut controller, 10)
+        );
+
+        // clear the display forever
+        let pc = crate::definitions::cpu::PROGRAM_COUNTER;
+        let rom = Rom::from_bytes("CLEAR", &[0x00, 0xE0, 0x12, 0x00]);
+        controller.set_rom(rom).unwrap();
+        controller.set_frame_skip(2);
+        assert_eq!(2, controller.frame_skip());
+
+        for _ in 0..4 {
+            assert_eq!(Ok(()), run_frame(&mut controller, 10));
+        }
+        // skipping every frame must not overflow
+        controller.set_frame_skip(usize::MAX);
+        assert_eq!(Ok(()), run_frame(&mut controller, 10));
+        // every cycle of the frames was run
+        let chip = controller.chipset().as_ref().unwrap();
+        assert_eq!(pc, chip.snapshot().program_counter);
+
+        let stats = controller.stats();
+        assert_eq!(10.0, stats.average_cycles_per_frame());
+        controller.reset_stats();
+        assert_eq!(0.0, controller.stats().average_cycles_per_frame());
+    }
+
+    #[test]
+    fn test_run_frame_paused() {
+        let mut mock_display = MockInternalDCommands::new();
+        // only the frame before the pause is drawn
+        mock_display.expect_display().times(1).return_const(());
+        let da = DisplayAdapter { da: mock_display };
+
+        let mut mock_keyboard = MockInternalKCommands::new();
+        mock_keyboard
+            .expect_get_keyboard()
+            .returning(|| Arc::new(RwLock::new(Keyboard::new())));
+        let ka = KeyboardAdapter { ka: mock_keyboard };
+
+        let mut controller: Controller<_, _, Worker, NoCallback> = Controller::new(da, ka);
+        let rom = Rom::from_bytes("CLEAR", &[0x00, 0xE0, 0x12, 0x00]);
+        controller.set_rom(rom).unwrap();
+        assert_eq!(Ok(()), run_frame(&mut controller, 3));
+
+        controller.chipset_mut().unwrap().pause();
+        let program_counter = controller
+            .chipset()
+            .as_ref()
+            .unwrap()
+            .snapshot()
+            .program_counter;
+        for _ in 0..4 {
+            assert_eq!(Ok(()), run_frame(&mut controller, 3));
+        }
+
+        assert_eq!(1, controller.frame);
+        assert_eq!(1, controller.stats.frames.len());
+        assert_eq!(0, controller.stats.cycles);
+        let chip = controller.chipset().as_ref().unwrap();
+        assert_eq!(program_counter, chip.snapshot().program_counter);
+    }
+
+    #[test]
+    fn test_runner_stats() {
+        let mut stats = RunnerStats::default();
+        assert_eq!(0.0, stats.instructions_per_second());
+        assert_eq!(0.0, stats.frames_per_second());
+        assert_eq!(0.0, stats.average_cycles_per_frame());
+
+        stats.end_frame();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        for _ in 0..10 {
+            stats.record_cycle();
+        }
+        stats.end_frame();
+
+        // two frames 20ms apart, the first one without any cycles
+        assert_eq!(5.0, stats.average_cycles_per_frame());
+        let fps = stats.frames_per_second();
+        assert!(fps > 0.0 && fps <= 50.0);
+        assert!((10.0 * fps - stats.instructions_per_second()).abs() < 1e-6);
+
+        // the window is limited
+        for _ in 0..(2 * STATS_WINDOW) {
+            stats.end_frame();
+        }
+        assert_eq!(STATS_WINDOW + 1, stats.frames.len());
+
+        stats.reset();
+        assert_eq!(0.0, stats.average_cycles_per_frame());
+    }
+
+    #[test]
+    fn test_diff_runner() {
+        const ROM_NAME: &str = "IBMLOGO";
+
+        let rom = crate::resources::RomArchives::new()
+            .get_file_data(ROM_NAME)
+            .expect("Something went wrong while extracting the rom");
+
+        let first: ChipSet<Worker, NoCallback> = ChipSet::new(rom.clone());
+        let second = ChipSet::new(rom.clone());
+        let mut runner = DiffRunner::new(first, second);
+        assert_eq!(Ok(None), runner.run(50));
+        assert_eq!(50, runner.cycles());
+
+        // the second chip loops forever, so that the first draw differs
+        let first: ChipSet<Worker, NoCallback> = ChipSet::new(rom.clone());
+        let second =
+            ChipSet::new(rom).with_opcode_at(crate::definitions::cpu::PROGRAM_COUNTER, 0x1200);
+        let mut runner = DiffRunner::new(first, second);
+        assert_eq!(Ok(Some(4)), runner.run(50));
     }
 }
@@ -1,6 +1,7 @@
 //! The countdown timers required by the Chip8 specification.
 use std::{
     sync::{
+        atomic::{AtomicBool, Ordering},
         mpsc::{self, RecvTimeoutError, SyncSender},
         Arc,
     },
@@ -37,6 +38,23 @@ pub trait TimedWorker {
     fn is_alive(&self) -> bool;
 }
 
+/// Is called with `true` once a timer starts counting down from zero and with
+/// `false` once it reaches zero again.
+pub type ChangeCallback = Box<dyn FnMut(bool) + Send>;
+
+/// The shared holder of the optional [`ChangeCallback`](ChangeCallback).
+type SharedChangeCallback = Arc<Mutex<Option<ChangeCallback>>>;
+
+/// Will call the change callback, if the timer started or stopped with the new value.
+fn notify_change<V: num::Unsigned + PartialEq>(on_change: &SharedChangeCallback, old: V, new: V) {
+    let active = new != V::zero();
+    if (old != V::zero()) != active {
+        if let Some(callback) = on_change.lock().as_mut() {
+            callback(active);
+        }
+    }
+}
+
 /// Empty implementation (default where there is no callback)
 pub struct NoCallback;
 
@@ -52,20 +70,23 @@ impl TimerCallback for NoCallback {
 pub struct TimerValue<V> {
     /// will store the value of the timer.
     value: Arc<RwLock<V>>,
+    /// Is called once the timer starts or stops.
+    on_change: SharedChangeCallback,
 }
 
 impl<V: num::Unsigned + Copy> TimerValue<V> {
     /// This create the TimerValue instance.
     /// Attention is is set to private, so that there can not be an instance created execept from
     /// [`Timer::new`](Timer::new).
-    fn new(value: Arc<RwLock<V>>) -> Self {
-        Self { value }
+    fn new(value: Arc<RwLock<V>>, on_change: SharedChangeCallback) -> Self {
+        Self { value, on_change }
     }
 
     /// Setter for the internal value.
     pub fn set_value(&mut self, value: V) {
         let mut val = self.value.write();
 
+        notify_change(&self.on_change, *val, value);
         *val = value;
     }
 
@@ -88,10 +109,14 @@ where
     /// infrastruture, it will count down to
     /// zero from what ever number given in
     /// the speck requireds 60Hz.
-    _worker: W,
+    worker: W,
     /// Is the optional function that might get called once the timer
     /// reaches zero.
     callback: Arc<Mutex<Option<S>>>,
+    /// Is called once the timer starts or stops.
+    on_change: SharedChangeCallback,
+    /// While set the timer will not count down.
+    paused: Arc<AtomicBool>,
 }
 impl<W, V> Timer<W, V, NoCallback>
 where
@@ -119,8 +144,16 @@ where
         let value = Arc::new(RwLock::new(value));
         let rw_value = value.clone();
         let ccb = cb.clone();
+        let paused = Arc::new(AtomicBool::new(false));
+        let cpaused = paused.clone();
+        let on_change: SharedChangeCallback = Arc::new(Mutex::new(None));
+        let con_change = on_change.clone();
 
         let func = move || {
+            if cpaused.load(Ordering::Relaxed) {
+                return;
+            }
+
             let mut cvalue = rw_value.write();
 
             let value = *cvalue;
@@ -137,6 +170,7 @@ where
             }
             if value > V::zero() {
                 *cvalue = value - V::one();
+                notify_change(&con_change, value, *cvalue);
             }
         };
 
@@ -145,10 +179,12 @@ where
         (
             Self {
                 value: value.clone(),
-                _worker: worker,
+                worker,
                 callback: cb,
+                on_change: on_change.clone(),
+                paused,
             },
-            TimerValue::new(value),
+            TimerValue::new(value, on_change),
         )
     }
 
@@ -168,13 +204,57 @@ where
     pub fn set_value(&mut self, value: V) {
         let mut val = self.value.write();
 
+        notify_change(&self.on_change, *val, value);
         *val = value;
     }
 
+    /// Will count the timer down by up to `ticks` intervals at once, stopping at zero, as
+    /// if the worker had run that often. Reaching zero handles the callback and reports
+    /// the change only once. This works while the timer is paused as well.
+    pub fn tick_by(&self, ticks: V) {
+        let mut value = self.value.write();
+        let old = *value;
+        if old == V::zero() {
+            return;
+        }
+        let new = if old > ticks { old - ticks } else { V::zero() };
+        if new == V::zero() {
+            if let Some(callback_handler) = self.callback.lock().as_mut() {
+                callback_handler.handle();
+            }
+        }
+        *value = new;
+        notify_change(&self.on_change, old, new);
+    }
+
+    /// Will set the callback, that is called once the timer starts counting down from
+    /// zero or reaches zero, replacing the previous one. The callback might be called
+    /// from the worker thread, while the timer value is locked, so that it must not
+    /// access the timer itself.
+    pub fn on_change(&self, callback: Option<ChangeCallback>) {
+        *self.on_change.lock() = callback;
+    }
+
     /// The getter fo the timer value at this current moment.
     pub fn get_value(&self) -> V {
         *self.value.read()
     }
+
+    /// Will pause or resume the count down of the timer.
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Checks if the count down of the timer is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Checks if the worker is still counting down the timer. The worker stops, if the
+    /// callback panics, after which the value stays the same forever.
+    pub fn is_alive(&self) -> bool {
+        self.worker.is_alive()
+    }
 }
 
 /// Is the internal worker, that exists on the
@@ -248,14 +328,13 @@ impl TimedWorker for Worker {
         // Will stop the worker, in two steps one by sending an empty message
         // and second by droping the only sender for the given receiver.
         if let Some(sender) = self.shutdown.take() {
-            sender
-                .send(())
-                .expect("This thread should be running here, but is not... Investigate.");
+            // the receiver is gone, if the thread panicked
+            let _ = sender.send(());
         }
         if let Some(thread) = self.thread.take() {
-            thread
-                .join()
-                .expect("Something went wrong with joining the worker thread.")
+            if thread.join().is_err() {
+                log::error!("The worker thread has panicked.");
+            }
         }
     }
 
@@ -279,17 +358,110 @@ impl Drop for Worker {
 mod tests {
     use super::*;
     use crate::definitions::timer;
+    use std::sync::atomic::AtomicUsize;
 
     #[test]
     fn test_timer() {
         let (mut timer, _): (Timer<Worker, u8, NoCallback>, _) =
             Timer::new(timer::HERZ, Duration::from_millis(timer::INTERVAL));
-        assert!(timer._worker.is_alive());
+        assert!(timer.worker.is_alive());
 
         std::thread::sleep(Duration::from_secs(1));
         assert_eq!(timer.get_value(), 0);
 
-        timer._worker.stop();
-        assert!(!timer._worker.is_alive());
+        timer.worker.stop();
+        assert!(!timer.worker.is_alive());
+    }
+
+    #[test]
+    fn test_timer_paused() {
+        let (mut timer, _): (Timer<Worker, u8, NoCallback>, _) =
+            Timer::new(0, Duration::from_millis(1));
+        timer.set_paused(true);
+        assert!(timer.is_paused());
+        timer.set_value(timer::HERZ);
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(timer.get_value(), timer::HERZ);
+
+        timer.set_paused(false);
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(timer.get_value(), 0);
+    }
+
+    #[test]
+    fn test_timer_tick_by() {
+        static HANDLED: AtomicUsize = AtomicUsize::new(0);
+        struct CountCallback;
+        impl TimerCallback for CountCallback {
+            fn new() -> Self {
+                Self
+            }
+            fn handle(&mut self) {
+                HANDLED.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let (mut timer, _): (Timer<Worker, u8, CountCallback>, _) =
+            Timer::with_callback(0, Duration::from_millis(1), CountCallback);
+        timer.set_paused(true);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let cchanges = changes.clone();
+        timer.on_change(Some(Box::new(move |active| cchanges.lock().push(active))));
+
+        timer.set_value(5);
+        timer.tick_by(2);
+        assert_eq!(timer.get_value(), 3);
+        assert_eq!(HANDLED.load(Ordering::Relaxed), 0);
+        // clamped at zero
+        timer.tick_by(10);
+        assert_eq!(timer.get_value(), 0);
+        timer.tick_by(1);
+
+        assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+        assert_eq!(vec![true, false], *changes.lock());
+    }
+
+    #[test]
+    fn test_timer_worker_panic() {
+        struct PanicCallback;
+        impl TimerCallback for PanicCallback {
+            fn new() -> Self {
+                Self
+            }
+            fn handle(&mut self) {
+                panic!("The callback failed.");
+            }
+        }
+
+        let (mut timer, _): (Timer<Worker, u8, PanicCallback>, _) =
+            Timer::with_callback(0, Duration::from_millis(1), PanicCallback);
+        assert!(timer.is_alive());
+
+        timer.set_value(2);
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!timer.is_alive());
+        // the value is no longer counted down
+        assert_eq!(timer.get_value(), 1);
+        // the dead worker can still be stopped and dropped
+        timer.worker.stop();
+    }
+
+    #[test]
+    fn test_timer_on_change() {
+        let (mut timer, mut value): (Timer<Worker, u8, NoCallback>, _) =
+            Timer::new(0, Duration::from_millis(1));
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let cchanges = changes.clone();
+        timer.on_change(Some(Box::new(move |active| cchanges.lock().push(active))));
+
+        timer.set_value(5);
+        // only the transitions are reported
+        value.set_value(10);
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(timer.get_value(), 0);
+        timer.set_value(0);
+
+        assert_eq!(vec![true, false], *changes.lock());
     }
 }
@@ -0,0 +1,150 @@
+//! Runs the same roms and inputs through differently created chips in lockstep, and
+//! checks that the display and the registers match after every cycle. All the ways of
+//! creating a chip have to end up with the same machine, so that a divergence (e.g. an
+//! opcode, that behaves differently depending on how the chip was set up) is caught.
+
+use chip::{
+    asm,
+    chip8::{ChipSet, ChipSetBuilder},
+    opcode::Operation,
+    resources::{Rom, RomArchives},
+    timer::{NoCallback, Worker},
+    ProcessError,
+};
+
+type Chip = ChipSet<Worker, NoCallback>;
+
+/// The amount of cycles every rom is run for.
+const CYCLES: usize = 2_000;
+
+/// The roms of the archive, that do not use `CXNN`, as the random numbers differ
+/// between the chips.
+const ROM_NAMES: [&str; 6] = ["CONNECT4", "IBMLOGO", "KALEID", "MISSILE", "TICTAC", "VERS"];
+
+/// An arithmetic loop, that runs into the carries and borrows of the `8XYN` opcodes and
+/// draws its intermediate results.
+const ARITHMETIC: &str = "
+        LD V0, 0xF0
+        LD V1, 0x11
+        LD V4, 0
+        LD V5, 0
+loop:   ADD V0, V1
+        LD V6, VF
+        SUB V1, V0
+        LD V7, VF
+        SUBN V2, V0
+        SHR V0
+        SHL V1
+        LD I, 0x300
+        LD B, V0
+        LD V3, [I]
+        LD F, V3
+        DRW V4, V5, 5
+        ADD V4, V6
+        ADD V5, V7
+        ADD V0, 3
+        JP loop
+";
+
+/// Will return the different ways of creating a chip running the given rom.
+fn variants(rom: &Rom) -> Vec<(&'static str, Chip)> {
+    let variants = vec![
+        ("new", ChipSet::new(rom.clone())),
+        (
+            // the debugging aids must not change the execution
+            "builder",
+            ChipSetBuilder::new(rom.clone())
+                .with_pc_history(64)
+                .with_data_execution_check(true)
+                .with_draw_deltas(true)
+                .build(),
+        ),
+        (
+            "fork",
+            ChipSet::new_empty().fork_with_rom(rom.clone()).unwrap(),
+        ),
+        (
+            "snapshot",
+            ChipSet::from_snapshot(Chip::new(rom.clone()).snapshot()).unwrap(),
+        ),
+    ];
+
+    variants
+        .into_iter()
+        .map(|(name, mut chip)| {
+            // the timers count down on their own, so that they have to be frozen
+            chip.pause();
+            // the roms run with the same quirks as in the frontends
+            chip.set_quirks(RomArchives::quirks());
+            (name, chip)
+        })
+        .collect()
+}
+
+/// The amount of cycles after which the next key is pressed.
+const KEY_PERIOD: usize = 50;
+
+/// Will press a different key at the start of every period and hold it for a few cycles,
+/// so that roms waiting for input progress.
+fn keys(cycle: usize) -> u16 {
+    match cycle % KEY_PERIOD {
+        0..=9 => 1 << (cycle / KEY_PERIOD % 16),
+        _ => 0,
+    }
+}
+
+/// Will run all the variants in lockstep and compare them to the first one after every
+/// cycle.
+fn assert_conformance(name: &str, rom: &Rom) {
+    let mut variants = variants(rom);
+    let mut waiting = false;
+
+    for cycle in 0..CYCLES {
+        let keys = keys(cycle);
+        for (_, chip) in variants.iter_mut() {
+            chip.set_keys_from(|key| keys & (1 << key) != 0);
+        }
+        // like a host, a waiting chip is only stepped once a key is pressed
+        if waiting && (keys == 0 || cycle % KEY_PERIOD != 0) {
+            continue;
+        }
+        let results: Vec<Result<Operation, ProcessError>> =
+            variants.iter_mut().map(|(_, chip)| chip.step()).collect();
+
+        let (reference_name, reference) = &variants[0];
+        for ((variant, chip), result) in variants.iter().zip(&results).skip(1) {
+            let context = format!(
+                "{}: {} diverged from {} in cycle {}",
+                name, variant, reference_name, cycle
+            );
+            assert_eq!(results[0], *result, "{}", context);
+            assert_eq!(
+                reference.registers_array(),
+                chip.registers_array(),
+                "{}",
+                context
+            );
+            assert_eq!(reference.get_display(), chip.get_display(), "{}", context);
+        }
+
+        match results[0] {
+            Ok(Operation::Exit) | Err(_) => break,
+            Ok(operation) => waiting = operation == Operation::Wait,
+        }
+    }
+}
+
+#[test]
+fn test_archive_roms_conform() {
+    let mut archive = RomArchives::new();
+    for name in ROM_NAMES {
+        let rom = archive.get_file_data(name).unwrap();
+        assert_conformance(name, &rom);
+    }
+}
+
+#[test]
+fn test_arithmetic_conforms() {
+    let rom = Rom::from_bytes("ARITHMETIC", &asm::assemble(ARITHMETIC).unwrap());
+    assert_conformance("ARITHMETIC", &rom);
+}
@@ -6,10 +6,81 @@ use chip::{
     timer::TimerCallback,
 };
 
+/// How the display of the chip is transformed before it is presented, so that it fits
+/// displays which are mounted rotated or mirrored. The game coordinates are unchanged.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub(crate) enum Transform {
+    #[default]
+    None,
+    /// Rotates clockwise by 90 degrees.
+    Rotate90,
+    /// Rotates by 180 degrees.
+    Rotate180,
+    /// Rotates clockwise by 270 degrees.
+    Rotate270,
+    /// Mirrors the left and the right side.
+    FlipHorizontal,
+    /// Mirrors the top and the bottom.
+    FlipVertical,
+}
+
+impl Transform {
+    /// All the available transformations.
+    pub const ALL: [Transform; 6] = [
+        Transform::None,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+    ];
+
+    /// The name shown to the user.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Transform::None => "None",
+            Transform::Rotate90 => "Rotate 90°",
+            Transform::Rotate180 => "Rotate 180°",
+            Transform::Rotate270 => "Rotate 270°",
+            Transform::FlipHorizontal => "Flip horizontally",
+            Transform::FlipVertical => "Flip vertically",
+        }
+    }
+
+    /// Will transform the given rows of pixels.
+    pub fn apply(&self, pixels: &[Vec<bool>]) -> Vec<Vec<bool>> {
+        let height = pixels.len();
+        let width = pixels.first().map_or(0, |row| row.len());
+
+        let (out_width, out_height) = match self {
+            Transform::Rotate90 | Transform::Rotate270 => (height, width),
+            _ => (width, height),
+        };
+
+        (0..out_height)
+            .map(|y| {
+                (0..out_width)
+                    .map(|x| match self {
+                        Transform::None => pixels[y][x],
+                        Transform::Rotate90 => pixels[height - 1 - x][y],
+                        Transform::Rotate180 => pixels[height - 1 - y][width - 1 - x],
+                        Transform::Rotate270 => pixels[x][width - 1 - y],
+                        Transform::FlipHorizontal => pixels[y][width - 1 - x],
+                        Transform::FlipVertical => pixels[height - 1 - y][x],
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, PartialEq, Default)]
 pub(crate) struct DisplayState {
     state: Vec<Vec<bool>>,
     changes: Vec<Vec<bool>>,
+    /// The display as drawn by the chip, before it was transformed.
+    pixels: Vec<Vec<bool>>,
+    transform: Transform,
 }
 
 impl DisplayState {
@@ -17,14 +88,63 @@ impl DisplayState {
         let len_o = state.len();
         let len_i = state[0].len();
         Self {
+            pixels: state.clone(),
             state,
             changes: vec![vec![false; len_i]; len_o],
+            transform: Transform::None,
         }
     }
 
     pub fn state(&self) -> &[Vec<bool>] {
         &self.state
     }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// Will change the transformation and apply it to the current display.
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        self.render();
+    }
+
+    /// Will transform the pixels into the presented state.
+    fn render(&mut self) {
+        let transformed = self.transform.apply(&self.pixels);
+
+        // the dimensions change with a rotation, so that everything changed
+        if transformed.len() != self.state.len()
+            || transformed.first().map(Vec::len) != self.state.first().map(Vec::len)
+        {
+            self.changes = transformed
+                .iter()
+                .map(|row| vec![true; row.len()])
+                .collect();
+            self.state = transformed;
+            return;
+        }
+
+        for (back_row, front_row, changes_row) in
+            itertools::izip!(transformed, self.state.iter_mut(), self.changes.iter_mut())
+        {
+            for (back_cell, front_cell, changes_cell) in
+                itertools::izip!(back_row, front_row.iter_mut(), changes_row.iter_mut())
+            {
+                // if there is a difference then we know that
+                // that given cell has updated
+                let state = back_cell != *front_cell;
+
+                // update the state if needed
+                if state {
+                    *front_cell = back_cell;
+                }
+
+                // make sure that we flag the needed cell
+                *changes_cell = state;
+            }
+        }
+    }
 }
 
 /// Translates the internal commands into the external ones.
@@ -64,34 +184,10 @@ impl DisplayCommands for DisplayAdapter {
             // update parent
             let mut display_state = self.display_state.borrow_mut();
 
-            let DisplayState {
-                state: elements,
-                changes,
-            } = &mut *display_state;
-
-            for (back_row, front_row, changes_row) in itertools::izip!(
-                pixels.as_ref().iter(),
-                elements.iter_mut(),
-                changes.iter_mut()
-            ) {
-                for (&back_cell, front_cell, changes_cell) in itertools::izip!(
-                    back_row.as_ref().iter(),
-                    front_row.iter_mut(),
-                    changes_row.iter_mut()
-                ) {
-                    // if there is a difference then we know that
-                    // that given cell has updated
-                    let state = back_cell != *front_cell;
-
-                    // update the state if needed
-                    if state {
-                        *front_cell = back_cell;
-                    }
-
-                    // make sure that we flag the needed cell
-                    *changes_cell = state;
-                }
+            for (row, new_row) in display_state.pixels.iter_mut().zip(pixels.as_ref()) {
+                row.copy_from_slice(new_row.as_ref());
             }
+            display_state.render();
         }
 
         self.callback.emit(());
@@ -104,6 +200,8 @@ impl DisplayCommands for DisplayAdapter {
 pub(crate) struct KeyboardAdapter {
     /// Stores the keyboard into to which the values are changed.
     keyboard: Arc<RwLock<Keyboard>>,
+    /// If a key stays pressed until it is pressed again.
+    sticky: bool,
 }
 
 impl KeyboardAdapter {
@@ -120,6 +218,27 @@ impl KeyboardAdapter {
         self.keyboard.write()
     }
 
+    /// Will enable or disable the sticky keys, where a key press toggles the key instead
+    /// of holding it, so that multiple keys can be pressed without holding them
+    /// simultaneously. Disabling the sticky keys releases all the keys.
+    pub fn set_sticky(&mut self, sticky: bool) {
+        if self.sticky && !sticky {
+            self.get_keyboard_write().set_bitmask(0);
+        }
+        self.sticky = sticky;
+    }
+
+    /// Will handle a key event, with the sticky keys only a press is considered and
+    /// inverts the current state of the key.
+    pub fn handle_key(&mut self, key: usize, pressed: bool) {
+        if !self.sticky {
+            self.set_key(key, pressed);
+        } else if pressed {
+            let current = self.get_keyboard_read().is_pressed(key);
+            self.set_key(key, !current);
+        }
+    }
+
     pub fn map_key(key: &str) -> Option<usize> {
         use std::collections::HashMap;
         /// maps the external keyboard layout to the internaly given.
@@ -171,3 +290,72 @@ impl TimerCallback for SoundCallback {
         log::debug!("There should be a sound played here... but that is too much work");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transform_apply() {
+        let pixels = vec![vec![true, false, false], vec![true, true, false]];
+
+        let cases = [
+            (Transform::None, pixels.clone()),
+            (
+                Transform::Rotate90,
+                vec![vec![true, true], vec![true, false], vec![false, false]],
+            ),
+            (
+                Transform::Rotate180,
+                vec![vec![false, true, true], vec![false, false, true]],
+            ),
+            (
+                Transform::Rotate270,
+                vec![vec![false, false], vec![false, true], vec![true, true]],
+            ),
+            (
+                Transform::FlipHorizontal,
+                vec![vec![false, false, true], vec![false, true, true]],
+            ),
+            (
+                Transform::FlipVertical,
+                vec![vec![true, true, false], vec![true, false, false]],
+            ),
+        ];
+
+        for (transform, expected) in cases {
+            assert_eq!(expected, transform.apply(&pixels), "{}", transform.name());
+        }
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut adapter = KeyboardAdapter::new();
+        let keyboard = adapter.get_keyboard();
+        let is_pressed = |key| keyboard.read().is_pressed(key);
+
+        // without sticky keys a key is held until it is released
+        adapter.handle_key(0x1, true);
+        assert!(is_pressed(0x1));
+        adapter.handle_key(0x2, true);
+        assert!(is_pressed(0x1) && is_pressed(0x2));
+        adapter.handle_key(0x1, false);
+        assert!(!is_pressed(0x1) && is_pressed(0x2));
+        adapter.handle_key(0x2, false);
+        assert!(!is_pressed(0x2));
+
+        // with sticky keys a press toggles the key and a release is ignored
+        adapter.set_sticky(true);
+        adapter.handle_key(0x3, true);
+        adapter.handle_key(0x3, false);
+        assert!(is_pressed(0x3));
+        adapter.handle_key(0x4, true);
+        assert!(is_pressed(0x3) && is_pressed(0x4));
+        adapter.handle_key(0x3, true);
+        assert!(!is_pressed(0x3) && is_pressed(0x4));
+
+        // disabling the sticky keys releases all of them
+        adapter.set_sticky(false);
+        assert!(!is_pressed(0x4));
+    }
+}
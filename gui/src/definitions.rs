@@ -41,4 +41,22 @@ pub mod field {
     /// Attention the implemtnation is in reverse, so a not `active` cell is per this definition
     /// `alive`.
     pub const ACTIVE: &str = "alive";
+
+    /// The class of the message showing an error to the user.
+    pub const ERROR: &str = "error";
+
+    /// The text of the button enabling the sticky keys.
+    pub const STICKY_KEYS_OFF_TEXT: &str = "Sticky keys: off";
+    /// The text of the button disabling the sticky keys.
+    pub const STICKY_KEYS_ON_TEXT: &str = "Sticky keys: on";
+
+    /// How many image pixels are used for a single display pixel in a screenshot.
+    #[cfg(feature = "image")]
+    pub const SCREENSHOT_SCALE: usize = 10;
+    /// The text of the screenshot button.
+    #[cfg(feature = "image")]
+    pub const SCREENSHOT_TEXT: &str = "Screenshot";
+    /// The text of the button storing the state in the url.
+    #[cfg(feature = "url-state")]
+    pub const SHARE_TEXT: &str = "Share";
 }
@@ -1,3 +1,23 @@
+/// The problems shown to the user, instead of panicking the whole page.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum GuiError {
+    #[error("The rom '{0}' does not exist.")]
+    RomNotFound(String),
+    #[error("The rom has a size of {size} bytes, but at most {max} bytes fit into the memory.")]
+    RomTooLarge { size: usize, max: usize },
+    #[error("Unable to extract the rom from the archive <{0}>.")]
+    ArchiveCorrupt(String),
+}
+
+impl From<chip::ChipError> for GuiError {
+    fn from(err: chip::ChipError) -> Self {
+        match err {
+            chip::ChipError::Archive(err) => Self::ArchiveCorrupt(err.to_string()),
+            chip::ChipError::RomSize { size, max } => Self::RomTooLarge { size, max },
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum WasmWorkerError {
     #[error("Unable to start worker, as worker is already running.")]
@@ -0,0 +1,70 @@
+//! Functions exported to JS, so that the emulator can be debugged step by step from the
+//! browser console. The debugged chip is independent from the one shown in the page.
+use std::cell::RefCell;
+
+use chip::{chip8::ChipSet, opcode::Operation, resources::RomArchives, timer::NoCallback};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::timer::TimingWorker;
+
+type DebugChip = ChipSet<TimingWorker, NoCallback>;
+
+thread_local! {
+    /// The chip debugged with the exported functions.
+    static CHIP: RefCell<Option<DebugChip>> = const { RefCell::new(None) };
+}
+
+/// The state reported after every step.
+#[derive(Debug, Serialize)]
+struct Report {
+    /// The program counter after the step.
+    pc: usize,
+    /// The executed opcode.
+    opcode: u16,
+    /// The operation requested by the chip in its debug form, one of `None`, `Wait`,
+    /// `Draw`, `Clear`, `Exit` or a scroll like `Scroll { dx: 4, dy: 0 }`.
+    operation: String,
+    /// The registers `V0` to `VF` after the step.
+    registers: Vec<u8>,
+    /// If the display was changed by the step.
+    display_changed: bool,
+}
+
+/// Will load the rom with the given name into the debugged chip.
+#[wasm_bindgen]
+pub fn debug_load(name: &str) -> Result<(), JsValue> {
+    let chip = RomArchives::new()
+        .load(name)
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    CHIP.with(|cell| *cell.borrow_mut() = Some(chip));
+    Ok(())
+}
+
+/// Will execute a single opcode of the debugged chip and return the resulting state as
+/// `{ pc, opcode, operation, registers, display_changed }`.
+#[wasm_bindgen]
+pub fn step_and_report() -> Result<JsValue, JsValue> {
+    CHIP.with(|cell| {
+        let mut chip = cell.borrow_mut();
+        let chip = chip
+            .as_mut()
+            .ok_or_else(|| JsValue::from_str("There is no rom loaded, use `debug_load`."))?;
+
+        let report = chip
+            .debug_step()
+            .map_err(|err| JsValue::from_str(&err.to_string()))?;
+        let report = Report {
+            pc: report.program_counter,
+            opcode: report.opcode,
+            operation: format!("{:?}", report.operation),
+            registers: report.registers.to_vec(),
+            display_changed: matches!(
+                report.operation,
+                Operation::Draw | Operation::Clear | Operation::Scroll { .. }
+            ),
+        };
+
+        serde_wasm_bindgen::to_value(&report).map_err(Into::into)
+    })
+}
@@ -1,8 +1,14 @@
 mod adapter;
 mod definitions;
 mod error;
+#[cfg(feature = "exported")]
+mod exported;
 mod model;
+#[cfg(feature = "image")]
+mod screenshot;
 mod timer;
+#[cfg(feature = "url-state")]
+mod url_state;
 
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
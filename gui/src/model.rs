@@ -1,12 +1,18 @@
+// The code generated by the `html!` macro binds the properties of the components without
+// fields to a unit value, and the `custom_debug::Debug` derive implements the trait from
+// within a constant. Neither can be allowed on the items themselves.
+#![allow(clippy::let_unit_value, non_local_definitions)]
+
 use std::{cell::RefCell, rc::Rc};
 
-use chip::{devices::KeyboardCommands, resources::RomArchives};
+use chip::resources::RomArchives;
 use yew::{
     classes, function_component, html, Callback, Component, Context, Html, Properties, TargetCast,
 };
 
 use crate::{
-    adapter::{DisplayAdapter, DisplayState, KeyboardAdapter, SoundCallback},
+    adapter::{DisplayAdapter, DisplayState, KeyboardAdapter, SoundCallback, Transform},
+    error::GuiError,
     timer::TimingWorker,
 };
 
@@ -21,8 +27,14 @@ pub fn app() -> Html {
 pub enum Msg {
     Roms(usize),
     Keyboard(yew::KeyboardEvent, bool),
+    StickyKeys,
     Display,
+    Transform(Transform),
     Tick,
+    #[cfg(feature = "image")]
+    Screenshot,
+    #[cfg(feature = "url-state")]
+    Share,
 }
 
 /// 1S in millis
@@ -56,6 +68,10 @@ type Controller = chip::Controller<DisplayAdapter, KeyboardAdapter, TimingWorker
 struct State {
     props: Props,
     keyboard_callbacks: KeyboardCallbacks,
+    /// If the sticky keys of the keyboard adapter are enabled.
+    sticky_keys: bool,
+    /// The last error, that is shown to the user.
+    error: Option<GuiError>,
     #[debug(skip)]
     tick_timer: Option<gloo::timers::callback::Interval>,
     #[debug(skip)]
@@ -107,19 +123,31 @@ impl Component for State {
             KeyboardCallbacks { key_up, key_down }
         };
 
-        let controller = chip::Controller::new(da, ka);
+        let mut controller = chip::Controller::new(da, ka);
+        // only the roms of the archive can be picked
+        controller.set_quirks(RomArchives::quirks());
 
         let props = Props {
             field: field_prop,
             rom: rom_props,
         };
 
-        Self {
+        #[allow(unused_mut)]
+        let mut state = Self {
             props,
             controller,
             keyboard_callbacks,
+            sticky_keys: false,
+            error: None,
             tick_timer: Default::default(),
+        };
+
+        #[cfg(feature = "url-state")]
+        if let Some(snapshot) = crate::url_state::load() {
+            state.load_snapshot(ctx, snapshot);
         }
+
+        state
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
@@ -128,10 +156,24 @@ impl Component for State {
                 log::debug!("Update Display");
                 true
             }
+            Msg::Transform(transform) => {
+                self.props
+                    .field
+                    .display
+                    .borrow_mut()
+                    .set_transform(transform);
+                true
+            }
             Msg::Keyboard(event, pressed) => {
                 handle_keypress(event, self.controller.keyboard(), pressed);
                 false
             }
+            Msg::StickyKeys => {
+                self.sticky_keys = !self.sticky_keys;
+                self.controller.keyboard().set_sticky(self.sticky_keys);
+                log::debug!("sticky keys enabled <{}>", self.sticky_keys);
+                true
+            }
             Msg::Roms(new) => {
                 // update rom state
                 self.props.rom.roms.chosen = Some(new);
@@ -139,19 +181,16 @@ impl Component for State {
                 log::debug!("name is <{}>", name);
 
                 // setup correct rom
-                let mut ra = RomArchives::new();
-                let rom = ra.get_file_data(name);
-                let rom = rom.expect("Able to correctly unwrap this rom file");
-                self.controller.set_rom(rom);
-
-                // setup timer callback
-                let callback = ctx.link().callback(|_| Msg::Tick);
-                let callback = move || callback.emit(());
-                let tt = &mut self.tick_timer;
-                *tt = Some(gloo::timers::callback::Interval::new(
-                    CHIP_DUR as _,
-                    callback,
-                ));
+                match load_rom(&mut self.controller, name) {
+                    Ok(()) => {
+                        self.error = None;
+                        self.start_ticking(ctx);
+                    }
+                    Err(err) => {
+                        log::error!("Unable to load the rom <{}>", err);
+                        self.error = Some(err);
+                    }
+                }
 
                 true
             }
@@ -171,15 +210,72 @@ impl Component for State {
                 }
                 false
             }
+            #[cfg(feature = "image")]
+            Msg::Screenshot => {
+                save_screenshot(&self.props.field);
+                false
+            }
+            #[cfg(feature = "url-state")]
+            Msg::Share => {
+                if let Some(chipset) = self.controller.chipset() {
+                    if let Err(err) = crate::url_state::save(&chipset.snapshot()) {
+                        log::error!("Unable to store the state in the url <{:?}>", err);
+                    }
+                }
+                false
+            }
         }
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> Html {
+    fn view(&self, ctx: &Context<Self>) -> Html {
         let props_rom = self.props.rom.clone();
         let props_field = &self.props.field;
+        let props_transform = TransformProp {
+            callback: ctx.link().callback(Msg::Transform),
+            chosen: props_field.display.borrow().transform(),
+        };
         let onkeyup = self.keyboard_callbacks.key_up.clone();
         let onkeydown = self.keyboard_callbacks.key_down.clone();
 
+        let sticky_keys = {
+            let onclick = ctx.link().callback(|_| Msg::StickyKeys);
+            let text = if self.sticky_keys {
+                crate::definitions::field::STICKY_KEYS_ON_TEXT
+            } else {
+                crate::definitions::field::STICKY_KEYS_OFF_TEXT
+            };
+            html! {
+                <button onclick = {onclick}>{ text }</button>
+            }
+        };
+
+        #[cfg(feature = "image")]
+        let screenshot = {
+            let onclick = ctx.link().callback(|_| Msg::Screenshot);
+            html! {
+                <button onclick = {onclick}>{ crate::definitions::field::SCREENSHOT_TEXT }</button>
+            }
+        };
+        #[cfg(not(feature = "image"))]
+        let screenshot = html! {};
+
+        #[cfg(feature = "url-state")]
+        let share = {
+            let onclick = ctx.link().callback(|_| Msg::Share);
+            html! {
+                <button onclick = {onclick}>{ crate::definitions::field::SHARE_TEXT }</button>
+            }
+        };
+        #[cfg(not(feature = "url-state"))]
+        let share = html! {};
+
+        let error = match &self.error {
+            Some(err) => html! {
+                <p class = {crate::definitions::field::ERROR}>{ err.to_string() }</p>
+            },
+            None => html! {},
+        };
+
         // tabindex='0' is need to make the div selectable
         // => so that the key event will fire
         html! {
@@ -187,12 +283,56 @@ impl Component for State {
                 <keyboard_helper::KeyboardHelp />
                 <h1>{ "Chip8 Emulator" }</h1>
                 <RomDropdown ..props_rom />
+                { error }
+                { sticky_keys }
+                { screenshot }
+                { share }
+                <TransformDropdown ..props_transform />
                 { draw_field(props_field) }
             </ div>
         }
     }
 }
 
+impl State {
+    /// Will start running the loaded rom.
+    fn start_ticking(&mut self, ctx: &Context<Self>) {
+        // setup timer callback
+        let callback = ctx.link().callback(|_| Msg::Tick);
+        let callback = move || callback.emit(());
+        self.tick_timer = Some(gloo::timers::callback::Interval::new(
+            CHIP_DUR as _,
+            callback,
+        ));
+    }
+
+    /// Will restore the state of the snapshot and start running it.
+    #[cfg(feature = "url-state")]
+    fn load_snapshot(&mut self, ctx: &Context<Self>, snapshot: chip::chip8::Snapshot) {
+        let roms = &mut self.props.rom.roms;
+        roms.chosen = roms
+            .files
+            .iter()
+            .position(|name| *name == snapshot.rom_name);
+
+        match self.controller.load_snapshot(snapshot) {
+            Ok(()) => self.start_ticking(ctx),
+            Err(err) => log::error!("Unable to restore the state <{}>", err),
+        }
+    }
+}
+
+/// Will extract the rom with the given name and load it into the controller.
+fn load_rom(controller: &mut Controller, name: &str) -> Result<(), GuiError> {
+    let mut ra = RomArchives::new();
+    if !ra.file_names().contains(&name) {
+        return Err(GuiError::RomNotFound(name.to_string()));
+    }
+    let rom = ra.get_file_data(name).map_err(chip::ChipError::from)?;
+    controller.set_rom(rom)?;
+    Ok(())
+}
+
 fn handle_keypress(event: yew::KeyboardEvent, ka: &mut KeyboardAdapter, pressed: bool) {
     if event.repeat() {
         return;
@@ -206,7 +346,7 @@ fn handle_keypress(event: yew::KeyboardEvent, ka: &mut KeyboardAdapter, pressed:
             key,
             pressed
         );
-        ka.set_key(key, pressed);
+        ka.handle_key(key, pressed);
     }
 }
 
@@ -294,6 +434,43 @@ fn draw_dropdown(props: &RomProp) -> Html {
     }
 }
 
+#[derive(Debug, PartialEq, Properties, Clone)]
+struct TransformProp {
+    callback: Callback<Transform>,
+    chosen: Transform,
+}
+
+#[function_component(TransformDropdown)]
+fn draw_transform_dropdown(props: &TransformProp) -> Html {
+    let items = Transform::ALL.iter().map(|transform| {
+        let selected = *transform == props.chosen;
+        html! {
+            <option selected = {selected} > { transform.name() } </option>
+        }
+    });
+
+    let callback = props.callback.clone();
+    let callback = move |event: yew::Event| {
+        if let Some(input) = event.target_dyn_into::<web_sys::HtmlSelectElement>() {
+            let val = input.selected_index();
+            if let Some(&transform) = usize::try_from(val)
+                .ok()
+                .and_then(|val| Transform::ALL.get(val))
+            {
+                callback.emit(transform);
+            }
+        } else {
+            log::warn!("Unable to cast");
+        }
+    };
+
+    html! {
+        <select name = { "transform" } onchange = { Callback::from(callback) }>
+            { for items }
+        </select>
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Properties)]
 struct FieldProp {
     display: Rc<RefCell<DisplayState>>,
@@ -328,6 +505,24 @@ fn draw_field(prop: &FieldProp) -> Html {
     }
 }
 
+/// Will save the current display as a png screenshot.
+#[cfg(feature = "image")]
+fn save_screenshot(prop: &FieldProp) {
+    use crate::{definitions::field, screenshot};
+
+    let display = prop.display.borrow();
+    let state = display.state();
+
+    let height = state.len();
+    let width = state.first().map_or(0, |row| row.len());
+    let frame: Vec<bool> = state.iter().flatten().copied().collect();
+
+    let data = screenshot::display_to_png(&frame, (width, height), field::SCREENSHOT_SCALE);
+    if let Err(err) = screenshot::download_png(&data, screenshot::FILE_NAME) {
+        log::error!("Unable to save the screenshot <{:?}>", err);
+    }
+}
+
 mod keyboard_helper {
     use crate::definitions::keyboard;
     use yew::{function_component, html, Properties};
@@ -0,0 +1,90 @@
+//! Saving the display as a png screenshot.
+use std::io::Cursor;
+
+use image::{DynamicImage, GrayImage, ImageOutputFormat, Luma};
+use wasm_bindgen::{JsCast, JsValue};
+
+/// The color of a pixel that is set.
+const PIXEL_ON: Luma<u8> = Luma([u8::MAX]);
+/// The color of a pixel that is not set.
+const PIXEL_OFF: Luma<u8> = Luma([u8::MIN]);
+
+/// The name of the downloaded screenshot.
+pub const FILE_NAME: &str = "chip8.png";
+
+/// Will encode the given frame as png, where every pixel is scaled up to a
+/// `scale x scale` square.
+///
+/// - `frame` - The pixels of the display row by row.
+/// - `dims` - The `(width, height)` of the display.
+/// - `scale` - How many image pixels are used for a single display pixel.
+pub fn display_to_png(frame: &[bool], (width, height): (usize, usize), scale: usize) -> Vec<u8> {
+    assert_eq!(
+        frame.len(),
+        width * height,
+        "The frame does not fit the given dimensions."
+    );
+
+    let image = GrayImage::from_fn((width * scale) as _, (height * scale) as _, |x, y| {
+        let (x, y) = (x as usize / scale, y as usize / scale);
+        if frame[y * width + x] {
+            PIXEL_ON
+        } else {
+            PIXEL_OFF
+        }
+    });
+
+    let mut data = Vec::new();
+    // writing a png into memory can only fail if the allocation fails
+    DynamicImage::ImageLuma8(image)
+        .write_to(&mut Cursor::new(&mut data), ImageOutputFormat::Png)
+        .expect("Unable to encode the display as png.");
+    data
+}
+
+/// Will let the browser download the given png data.
+pub fn download_png(data: &[u8], name: &str) -> Result<(), JsValue> {
+    let array = js_sys::Uint8Array::from(data);
+    let parts = js_sys::Array::of1(&array);
+
+    let properties = web_sys::BlobPropertyBag::new();
+    properties.set_type("image/png");
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &properties)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("There is no document available."))?;
+    let anchor: web_sys::HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::ImageFormat;
+
+    #[test]
+    fn test_display_to_png() {
+        // a 3x2 display with the top left and the bottom right pixel set
+        let frame = [true, false, false, false, false, true];
+        let data = display_to_png(&frame, (3, 2), 2);
+
+        let image = image::load_from_memory_with_format(&data, ImageFormat::Png)
+            .expect("a valid png")
+            .to_luma8();
+        assert_eq!((6, 4), image.dimensions());
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let expected = if frame[(y / 2 * 3 + x / 2) as usize] {
+                PIXEL_ON
+            } else {
+                PIXEL_OFF
+            };
+            assert_eq!(expected, *pixel, "pixel ({}, {})", x, y);
+        }
+    }
+}
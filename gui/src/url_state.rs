@@ -0,0 +1,82 @@
+//! Sharing the state of the chip with a link, by storing a snapshot in the url hash.
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chip::chip8::Snapshot;
+use wasm_bindgen::JsValue;
+
+/// Will encode the snapshot, so that it can be used as the url hash.
+pub fn encode(snapshot: &Snapshot) -> String {
+    URL_SAFE_NO_PAD.encode(snapshot.to_compressed())
+}
+
+/// Will decode the snapshot from the url hash, the leading `#` is optional.
+pub fn decode(hash: &str) -> Option<Snapshot> {
+    let hash = hash.strip_prefix('#').unwrap_or(hash);
+    if hash.is_empty() {
+        return None;
+    }
+
+    let compressed = URL_SAFE_NO_PAD
+        .decode(hash)
+        .map_err(|err| log::warn!("Unable to decode the url hash <{}>", err))
+        .ok()?;
+    Snapshot::from_compressed(&compressed)
+        .map_err(|err| log::warn!("Unable to parse the snapshot <{}>", err))
+        .ok()
+}
+
+/// Will read the snapshot stored in the url hash of the current page.
+pub fn load() -> Option<Snapshot> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    decode(&hash)
+}
+
+/// Will store the snapshot in the url hash of the current page.
+pub fn save(snapshot: &Snapshot) -> Result<(), JsValue> {
+    web_sys::window()
+        .ok_or_else(|| JsValue::from_str("There is no window available."))?
+        .location()
+        .set_hash(&encode(snapshot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chip::{
+        chip8::ChipSet,
+        resources::RomArchives,
+        timer::{NoCallback, Worker},
+    };
+
+    /// Will return the snapshot of a rom, that has drawn a few sprites.
+    fn get_snapshot() -> Snapshot {
+        let mut chip: ChipSet<Worker, NoCallback> = RomArchives::new().load("IBMLOGO").unwrap();
+        for _ in 0..20 {
+            chip.step().unwrap();
+        }
+        chip.snapshot()
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let snapshot = get_snapshot();
+        let hash = encode(&snapshot);
+
+        assert_eq!(Some(&snapshot), decode(&hash).as_ref());
+        assert_eq!(Some(snapshot), decode(&format!("#{}", hash)));
+        assert_eq!(None, decode("#"));
+    }
+
+    #[test]
+    fn test_decode_corrupt() {
+        let hash = encode(&get_snapshot());
+
+        // not base64 at all
+        assert_eq!(None, decode("#not a snapshot"));
+        // cut off in the middle of the snapshot
+        assert_eq!(None, decode(&hash[..hash.len() / 2]));
+        assert_eq!(None, decode(&hash[..hash.len() - 4]));
+        // a different header
+        let first = if hash.starts_with('A') { "B" } else { "A" };
+        assert_eq!(None, decode(&format!("{}{}", first, &hash[1..])));
+    }
+}